@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+
+/// A late lead change pushed the auction deadline back.
+#[event]
+pub struct AuctionExtended {
+    pub auction: Pubkey,
+    pub new_end_time: i64,
+    /// Total seconds the auction has been extended by so far
+    pub extended_by: i64,
+}
+
+#[event]
+pub struct AuctionCreated {
+    pub auction: Pubkey,
+    pub maker: Pubkey,
+    pub nft_mint: Pubkey,
+    pub bid_mint: Pubkey,
+    pub prize_amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub reserve_price: u64,
+    pub buy_now_price: u64,
+    /// Bundle auctions only: the NFTs sold together with `nft_mint`
+    pub bundle_mints: Vec<Pubkey>,
+}
+
+#[event]
+pub struct BidPlaced {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    /// Amount added by this bid
+    pub amount: u64,
+    /// The bidder's cumulative total after this bid
+    pub total: u64,
+    /// Whether this bid took the top spot from someone else
+    pub leader_changed: bool,
+    /// Multi-winner auctions: whether this bid newly made the leaderboard.
+    /// Always false on single-winner auctions.
+    pub entered_leaderboard: bool,
+}
+
+/// A sealed bid was committed. Only the deposit is public at this point.
+#[event]
+pub struct BidCommitted {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub deposit: u64,
+}
+
+#[event]
+pub struct BidRevealed {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub leader_changed: bool,
+}
+
+/// Emitted by resolve_auction and buy_now. When `reserve_met` is false the
+/// NFT went back to the maker and no sale happened.
+#[event]
+pub struct AuctionResolved {
+    pub auction: Pubkey,
+    pub winner: Pubkey,
+    /// What the winner paid, which is the runner-up's bid under second-price settlement.
+    /// Zero when the reserve wasn't met and nothing sold.
+    pub amount: u64,
+    /// The mint `amount` and the cuts below are in
+    pub mint: Pubkey,
+    /// Protocol fee taken out of `amount`
+    pub fee: u64,
+    /// Creator royalties taken out of `amount`
+    pub royalties: u64,
+    pub reserve_met: bool,
+}
+
+/// A multi-winner auction paid out one unit to a winner at their own bid.
+#[event]
+pub struct PrizeClaimed {
+    pub auction: Pubkey,
+    pub winner: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub royalties: u64,
+}
+
+#[event]
+pub struct RefundClaimed {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    /// Amount returned to the bidder, after any penalty
+    pub amount: u64,
+    /// Kept by the maker for an unrevealed sealed bid
+    pub penalty: u64,
+}
+
+#[event]
+pub struct BidWithdrawn {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    /// Amount returned to the bidder, after any penalty
+    pub amount: u64,
+    /// Kept by the maker for withdrawing early
+    pub penalty: u64,
+}
+
+#[event]
+pub struct AuctionCancelled {
+    pub auction: Pubkey,
+    pub maker: Pubkey,
+}
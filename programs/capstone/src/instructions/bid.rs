@@ -1,10 +1,13 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{
-    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
+use super::collection_gate::verify_holder;
 use super::error::AuctionError;
-use crate::{Auction, Bids};
+use super::escrow::BidDeposit;
+use crate::{Auction, AuctionExtended, AuctionKind, BidPlaced, Bids};
 
 #[derive(Accounts)]
 pub struct Bid<'info> {
@@ -23,61 +26,199 @@ pub struct Bid<'info> {
     )]
     pub bid_record: Account<'info, Bids>,
 
+    /// Not needed on native-SOL auctions, where lamports come from `bidder`
     #[account(mut)]
-    pub bidder_bid_ata: InterfaceAccount<'info, TokenAccount>,
+    pub bidder_bid_ata: Option<InterfaceAccount<'info, TokenAccount>>,
 
+    /// One vault per accepted mint. Vaults for extra payment mints are
+    /// opened by the first bid in that mint.
     #[account(
-        mut,
+        init_if_needed,
+        payer = bidder,
         associated_token::mint = bid_mint,
         associated_token::authority = auction,
     )]
     pub vault_bid: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(address = auction.bid_mint)]
+    /// `auction.bid_mint` or one of its payment mints
+    #[account(constraint = auction.accepts(&bid_mint.key()) @ AuctionError::UnsupportedBidMint)]
     pub bid_mint: InterfaceAccount<'info, Mint>,
 
+    /// Holders-only auctions: a token account of the bidder's holding an NFT
+    /// from the required collection
+    pub holder_nft_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: Holders-only auctions: that NFT's metadata, checked in the handler
+    pub holder_nft_metadata: Option<UncheckedAccount<'info>>,
+
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info> Bid<'info> {
-    pub fn bid(&mut self, additional_amount: u64, bumps: &BidBumps) -> Result<()> {
-        // Enforce the time limit
+    /// `proof` places the bidder on the auction's allowlist, if it has one.
+    /// `hook_accounts` are forwarded to the bid mint's transfer hook, if any.
+    pub fn bid(
+        &mut self,
+        additional_amount: u64,
+        proof: &[[u8; 32]],
+        hook_accounts: &[AccountInfo<'info>],
+        bumps: &BidBumps,
+    ) -> Result<()> {
+        // Dutch auctions are bought outright through buy_now
+        require!(
+            self.auction.kind == AuctionKind::English,
+            AuctionError::NotEnglishAuction
+        );
+
+        // Enforce the bidding window
         let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= self.auction.start_time,
+            AuctionError::AuctionNotStarted
+        );
         require!(
             clock.unix_timestamp < self.auction.end_time,
             AuctionError::AuctionEnded
         );
 
+        require!(additional_amount > 0, AuctionError::ZeroBid);
+        verify_holder(
+            &self.auction,
+            &self.bidder.key(),
+            self.holder_nft_account.as_deref(),
+            self.holder_nft_metadata.as_ref(),
+        )?;
+        require!(
+            self.auction.allows(&self.bidder.key(), proof),
+            AuctionError::NotOnAllowlist
+        );
+
         // Initialize baseline data if this is a brand new bid
         if self.bid_record.amount == 0 {
             self.bid_record.bidder = self.bidder.key();
             self.bid_record.bump = bumps.bid_record;
             self.bid_record.refunded = false;
+            self.bid_record.mint = self.bid_mint.key();
         }
+        require_keys_eq!(
+            self.bid_record.mint,
+            self.bid_mint.key(),
+            AuctionError::BidMintMismatch
+        );
+
+        // Transferring the Bidder's tokens (or lamports) into escrow. Only
+        // what reaches the vault counts, so a transfer fee comes out of the bid.
+        let received = BidDeposit {
+            auction: &self.auction,
+            bidder: &self.bidder,
+            bidder_bid_ata: self.bidder_bid_ata.as_ref(),
+            vault_bid: &self.vault_bid,
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+            system_program: &self.system_program,
+            hook_accounts,
+        }
+        .collect(additional_amount)?;
+        require!(received > 0, AuctionError::ZeroBid);
 
         // Update the user's total deposited amount
-        self.bid_record.amount = self
-            .bid_record
-            .amount
-            .checked_add(additional_amount)
-            .unwrap();
-
-        // Updating the Auction leaderboard if they are the new highest bidder
-        if self.bid_record.amount > self.auction.highest_bid_amount {
-            self.auction.highest_bidder = self.bidder.key();
-            self.auction.highest_bid_amount = self.bid_record.amount;
+        self.bid_record.amount = self.bid_record.amount.checked_add(received).unwrap();
+
+        // Everything on the leaderboard is compared in bid_mint units
+        let total = self
+            .auction
+            .normalize(&self.bid_mint.key(), self.bid_record.amount);
+        // Rounding down at a small rate can leave a deposit worth nothing,
+        // which could never lead or rank
+        require!(total > 0, AuctionError::BidRoundsToZero);
+
+        require!(
+            total >= self.auction.bid_rules.start_price,
+            AuctionError::BidBelowStartPrice
+        );
+
+        // Updating the Auction leaderboard if they are the new highest bidder.
+        // The current leader may top up freely; anyone taking the lead from
+        // them has to clear the minimum increment.
+        let mut leader_changed = false;
+        let mut entered_leaderboard = false;
+        if self.auction.winners > 1 {
+            // Multi-winner: ranked bidders may top up freely, newcomers must
+            // beat the lowest ranked bid once every slot is taken
+            let ranked = self
+                .auction
+                .leaderboard
+                .iter()
+                .any(|entry| entry.bidder == self.bidder.key());
+            if ranked || total >= self.auction.min_leaderboard_bid() {
+                let previous_leader = self.auction.leaderboard.first().map(|entry| entry.bidder);
+                self.auction.rank(self.bidder.key(), total);
+                leader_changed = previous_leader != Some(self.bidder.key())
+                    && self.auction.highest_bidder == self.bidder.key();
+
+                // Every slot is a win here, so the soft close guards all of
+                // them: taking any slot late gives the others time to answer
+                entered_leaderboard = !ranked;
+                if entered_leaderboard {
+                    self.extend_if_sniped(clock.unix_timestamp);
+                }
+            }
+        } else if total > self.auction.highest_bid_amount {
+            leader_changed = self.auction.highest_bidder != self.bidder.key();
+            if leader_changed {
+                require!(
+                    total >= self.auction.min_leading_bid(),
+                    AuctionError::BidIncrementTooSmall
+                );
+            }
+            self.auction.take_lead(self.bidder.key(), total);
+
+            if leader_changed {
+                self.extend_if_sniped(clock.unix_timestamp);
+            }
+        } else if total > self.auction.second_highest_bid_amount {
+            self.auction.second_highest_bid_amount = total;
+        }
+
+        self.auction.escrowed = self.auction.escrowed.checked_add(received).unwrap();
+
+        emit!(BidPlaced {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            amount: received,
+            total: self.bid_record.amount,
+            leader_changed,
+            entered_leaderboard,
+        });
+
+        Ok(())
+    }
+
+    /// Soft close: a lead change inside the closing window pushes the
+    /// deadline back, up to the maker's optional cap.
+    fn extend_if_sniped(&mut self, now: i64) {
+        let soft_close = self.auction.soft_close;
+        if soft_close.extension == 0 || self.auction.end_time - now > soft_close.window {
+            return;
+        }
+
+        let mut extension = soft_close.extension;
+        if soft_close.max_extension > 0 {
+            extension = extension.min(soft_close.max_extension - self.auction.extended_by);
+        }
+        if extension <= 0 {
+            return;
         }
 
-        // Transferring tokens from the Bidder to the shared Vault
-        let transfer_accounts = TransferChecked {
-            from: self.bidder_bid_ata.to_account_info(),
-            to: self.vault_bid.to_account_info(),
-            mint: self.bid_mint.to_account_info(),
-            authority: self.bidder.to_account_info(),
-        };
+        self.auction.end_time += extension;
+        self.auction.extended_by += extension;
 
-        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
-        transfer_checked(cpi_ctx, additional_amount, self.bid_mint.decimals)
+        emit!(AuctionExtended {
+            auction: self.auction.key(),
+            new_end_time: self.auction.end_time,
+            extended_by: self.auction.extended_by,
+        });
     }
 }
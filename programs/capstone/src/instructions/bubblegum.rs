@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+use crate::CompressedNft;
+
+pub const BUBBLEGUM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const SPL_NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+pub const SPL_ACCOUNT_COMPRESSION_ID: Pubkey =
+    pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+/// Anchor discriminator of Bubblegum's `transfer` instruction
+const TRANSFER_DISCRIMINATOR: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+
+/// The accounts needed to move a compressed NFT through Bubblegum. Shared by
+/// the make, resolve and cancel instructions of compressed auctions.
+///
+/// Bubblegum verifies the leaf against `root` using the proof, which arrives
+/// as remaining accounts: one node per tree level above the canopy.
+pub(crate) struct Bubblegum<'a, 'info> {
+    pub tree_config: &'a AccountInfo<'info>,
+    pub merkle_tree: &'a AccountInfo<'info>,
+    pub log_wrapper: &'a AccountInfo<'info>,
+    pub compression_program: &'a AccountInfo<'info>,
+    pub bubblegum_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'info> Bubblegum<'_, 'info> {
+    /// Moves `leaf` from `owner`, who signs (directly or through
+    /// `signer_seeds`), to `new_owner`. `delegate` must be the leaf's current
+    /// delegate; Bubblegum resets it to the new owner.
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer(
+        &self,
+        owner: &AccountInfo<'info>,
+        delegate: &AccountInfo<'info>,
+        new_owner: &AccountInfo<'info>,
+        leaf: &CompressedNft,
+        root: [u8; 32],
+        proof: &[AccountInfo<'info>],
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.tree_config.key(), false),
+            AccountMeta::new_readonly(owner.key(), true),
+            AccountMeta::new_readonly(delegate.key(), false),
+            AccountMeta::new_readonly(new_owner.key(), false),
+            AccountMeta::new(self.merkle_tree.key(), false),
+            AccountMeta::new_readonly(self.log_wrapper.key(), false),
+            AccountMeta::new_readonly(self.compression_program.key(), false),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+        ];
+        accounts.extend(
+            proof
+                .iter()
+                .map(|node| AccountMeta::new_readonly(node.key(), false)),
+        );
+
+        let mut data = TRANSFER_DISCRIMINATOR.to_vec();
+        (
+            root,
+            leaf.data_hash,
+            leaf.creator_hash,
+            leaf.nonce,
+            leaf.index,
+        )
+            .serialize(&mut data)?;
+
+        let mut account_infos = vec![
+            self.tree_config.clone(),
+            owner.clone(),
+            delegate.clone(),
+            new_owner.clone(),
+            self.merkle_tree.clone(),
+            self.log_wrapper.clone(),
+            self.compression_program.clone(),
+            self.system_program.clone(),
+            self.bubblegum_program.clone(),
+        ];
+        account_infos.extend_from_slice(proof);
+
+        invoke_signed(
+            &Instruction {
+                program_id: BUBBLEGUM_ID,
+                accounts,
+                data,
+            },
+            &account_infos,
+            signer_seeds,
+        )
+        .map_err(Into::into)
+    }
+}
@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::{
+    create_idempotent, get_associated_token_address_with_program_id, Create,
+};
+use anchor_spl::token_interface::{close_account, CloseAccount, Mint, TransferChecked};
+
+use super::error::AuctionError;
+use super::transfer_hook::transfer_checked;
+use crate::MAX_BUNDLE_SIZE;
+
+/// The accounts needed to move the extra NFTs of a bundle auction in and out
+/// of their vaults. Shared by make_auction, resolve_auction and cancel_auction.
+///
+/// Bundle accounts arrive as remaining accounts, one `(mint, vault, ata)`
+/// triplet per extra NFT in `Auction::bundle_mints` order, where `ata` is the
+/// maker's source on deposit and the receiving owner's ATA on release.
+pub(crate) struct Bundle<'a, 'info> {
+    pub auction: &'a AccountInfo<'info>,
+    /// Pays rent for any ATA that has to be created
+    pub payer: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    /// Searched by address for the accounts a transfer hook needs
+    pub hook_accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'info> Bundle<'_, 'info> {
+    pub const ACCOUNTS_PER_MINT: usize = 3;
+
+    /// Creates a vault for each extra NFT and moves it in from the maker.
+    /// `excluded` holds mints a bundle item may not reuse (the primary NFT
+    /// and the bid mint). Returns the bundled mints in order.
+    pub fn deposit(
+        &self,
+        maker: &AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+        excluded: &[Pubkey],
+    ) -> Result<Vec<Pubkey>> {
+        require!(
+            accounts.len() % Self::ACCOUNTS_PER_MINT == 0,
+            AuctionError::InvalidBundleAccounts
+        );
+        require!(
+            accounts.len() / Self::ACCOUNTS_PER_MINT <= MAX_BUNDLE_SIZE,
+            AuctionError::BundleTooLarge
+        );
+
+        let mut mints: Vec<Pubkey> = Vec::new();
+        for item in accounts.chunks_exact(Self::ACCOUNTS_PER_MINT) {
+            let (mint_info, vault, maker_ata) = (&item[0], &item[1], &item[2]);
+
+            require!(
+                mint_info.owner == self.token_program.key,
+                AuctionError::InvalidBundleAccounts
+            );
+            let mint = Mint::try_deserialize(&mut &mint_info.try_borrow_data()?[..])?;
+            require!(
+                mint.decimals == 0 && mint.supply == 1,
+                AuctionError::InvalidNftMint
+            );
+            require!(
+                !excluded.contains(mint_info.key) && !mints.contains(mint_info.key),
+                AuctionError::DuplicateBundleMint
+            );
+
+            self.create_ata(vault, self.auction, mint_info)?;
+            transfer_checked(
+                CpiContext::new(
+                    self.token_program.clone(),
+                    TransferChecked {
+                        from: maker_ata.clone(),
+                        to: vault.clone(),
+                        mint: mint_info.clone(),
+                        authority: maker.clone(),
+                    },
+                )
+                .with_remaining_accounts(self.hook_accounts.to_vec()),
+                1,
+                0,
+            )?;
+
+            mints.push(mint_info.key());
+        }
+
+        Ok(mints)
+    }
+
+    /// Moves every extra NFT to `owner`'s ATA, creating it if needed, and
+    /// closes each vault with its rent going to `rent_destination`.
+    pub fn release(
+        &self,
+        mints: &[Pubkey],
+        accounts: &[AccountInfo<'info>],
+        owner: &AccountInfo<'info>,
+        rent_destination: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        require!(
+            accounts.len() >= mints.len() * Self::ACCOUNTS_PER_MINT,
+            AuctionError::InvalidBundleAccounts
+        );
+
+        for (mint, item) in mints
+            .iter()
+            .zip(accounts.chunks_exact(Self::ACCOUNTS_PER_MINT))
+        {
+            let (mint_info, vault, destination) = (&item[0], &item[1], &item[2]);
+            let expected_vault = get_associated_token_address_with_program_id(
+                self.auction.key,
+                mint,
+                self.token_program.key,
+            );
+            require!(
+                mint_info.key == mint && vault.key() == expected_vault,
+                AuctionError::InvalidBundleAccounts
+            );
+
+            // The ATA program rejects `destination` unless it is `owner`'s ATA
+            self.create_ata(destination, owner, mint_info)?;
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_program.clone(),
+                    TransferChecked {
+                        from: vault.clone(),
+                        to: destination.clone(),
+                        mint: mint_info.clone(),
+                        authority: self.auction.clone(),
+                    },
+                    signer_seeds,
+                )
+                .with_remaining_accounts(self.hook_accounts.to_vec()),
+                1,
+                0,
+            )?;
+            close_account(CpiContext::new_with_signer(
+                self.token_program.clone(),
+                CloseAccount {
+                    account: vault.clone(),
+                    destination: rent_destination.clone(),
+                    authority: self.auction.clone(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn create_ata(
+        &self,
+        ata: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        mint: &AccountInfo<'info>,
+    ) -> Result<()> {
+        create_idempotent(CpiContext::new(
+            self.associated_token_program.clone(),
+            Create {
+                payer: self.payer.clone(),
+                associated_token: ata.clone(),
+                authority: authority.clone(),
+                mint: mint.clone(),
+                system_program: self.system_program.clone(),
+                token_program: self.token_program.clone(),
+            },
+        ))
+    }
+}
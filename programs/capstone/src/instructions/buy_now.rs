@@ -0,0 +1,214 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::Metadata,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::error::AuctionError;
+use super::escrow::{gross_up, BidDeposit};
+use super::resolve::{Royalties, Settlement};
+use crate::{Auction, AuctionResolved, Bids, Config};
+
+/// Lets a bidder end the auction on the spot by meeting the maker's
+/// buy-now price, or the current price of a Dutch auction. The NFT and
+/// proceeds settle exactly like resolve_auction, and every other bidder can
+/// claim their refund straight away.
+#[derive(Accounts)]
+pub struct BuyNow<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: We only need this to validate the maker_bid_ata ownership
+    #[account(mut, address = auction.maker)]
+    pub maker: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub auction: Box<Account<'info, Auction>>,
+
+    /// Any amount the buyer already bid counts toward the buy-now price
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = Bids::DISCRIMINATOR.len() + Bids::INIT_SPACE,
+        seeds = [b"bids", auction.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub bid_record: Account<'info, Bids>,
+
+    /// Not needed on native-SOL auctions, where lamports come from `buyer`
+    #[account(mut)]
+    pub buyer_bid_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_nft_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = bid_mint,
+        associated_token::authority = maker,
+    )]
+    pub maker_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Validates the fee_recipient_bid_ata ownership, and receives the
+    /// fee directly on native-SOL auctions
+    #[account(mut, address = config.fee_recipient)]
+    pub fee_recipient: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer, // The protocol's cut of the sale lands here
+        associated_token::mint = bid_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_recipient_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_nft: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_bid: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = auction.nft_mint)]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(address = auction.bid_mint)]
+    pub bid_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Pinned to the NFT's metadata PDA. Royalties are only paid when it
+    /// holds Token Metadata data, so NFTs minted without metadata still settle.
+    #[account(
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref()],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub nft_metadata: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> BuyNow<'info> {
+    /// `remaining_accounts` works as in `ResolveAuction::resolve`, minus the
+    /// bundle accounts: creator accounts first, then any transfer-hook accounts.
+    pub fn buy_now(
+        &mut self,
+        remaining_accounts: &[AccountInfo<'info>],
+        bumps: &BuyNowBumps,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let instant_price = self
+            .auction
+            .instant_price(clock.unix_timestamp)
+            .ok_or(AuctionError::BuyNowDisabled)?;
+        // Bids may keep climbing past the buy-now price, and from then on
+        // buying outright would undercut the leader
+        require!(
+            instant_price > self.auction.highest_bid_amount,
+            AuctionError::BuyNowOutbid
+        );
+        require!(
+            clock.unix_timestamp >= self.auction.start_time,
+            AuctionError::AuctionNotStarted
+        );
+        require!(
+            clock.unix_timestamp < self.auction.end_time,
+            AuctionError::AuctionEnded
+        );
+        require!(!self.auction.resolved, AuctionError::AlreadyResolved);
+
+        // Initialize baseline data if this is a brand new bid
+        if self.bid_record.amount == 0 {
+            self.bid_record.bidder = self.buyer.key();
+            self.bid_record.bump = bumps.bid_record;
+            self.bid_record.refunded = false;
+            self.bid_record.mint = self.bid_mint.key();
+        }
+
+        // Top the buyer's existing bid up to the buy-now price, sending
+        // enough on top to cover any transfer fee on the way into the vault
+        let top_up = instant_price.saturating_sub(self.bid_record.amount);
+        let mut received = 0;
+        if top_up > 0 {
+            received = BidDeposit {
+                auction: &self.auction,
+                bidder: &self.buyer,
+                bidder_bid_ata: self.buyer_bid_ata.as_deref(),
+                vault_bid: &self.vault_bid,
+                bid_mint: &self.bid_mint,
+                token_program: &self.token_program,
+                system_program: &self.system_program,
+                hook_accounts: remaining_accounts,
+            }
+            .collect(gross_up(&self.bid_mint, top_up)?)?;
+        }
+        let price = self.bid_record.amount.checked_add(received).unwrap();
+        // The buyer's earlier bids and the top-up all leave escrow as the sale price
+        self.auction.escrowed -= self.bid_record.amount;
+
+        // Close the auction now: the buyer wins, and pulling end_time in
+        // opens claim_refund to everyone else immediately
+        self.auction.highest_bidder = self.buyer.key();
+        self.auction.highest_bid_amount = price;
+        self.auction.end_time = clock.unix_timestamp;
+        self.auction.resolved = true;
+
+        let seed_bytes = self.auction.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            self.auction.maker.as_ref(),
+            seed_bytes.as_ref(),
+            &[self.auction.bump],
+        ]];
+
+        let settlement = Settlement {
+            auction: &self.auction,
+            maker: &self.maker,
+            maker_bid_ata: &self.maker_bid_ata,
+            fee_recipient: &self.fee_recipient,
+            fee_recipient_bid_ata: &self.fee_recipient_bid_ata,
+            vault_nft: Some(&self.vault_nft),
+            vault_bid: &self.vault_bid,
+            nft_mint: Some(&self.nft_mint),
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+            royalties: Royalties::from_metadata(&self.nft_metadata)?,
+            creator_atas: remaining_accounts,
+            hook_accounts: remaining_accounts,
+        };
+        settlement.release_prize(self.buyer_nft_ata.to_account_info(), signer_seeds)?;
+        let (fee, royalties) = settlement.pay_out(price, signer_seeds)?;
+
+        emit!(AuctionResolved {
+            auction: self.auction.key(),
+            winner: self.buyer.key(),
+            amount: price,
+            mint: self.bid_mint.key(),
+            fee,
+            royalties,
+            reserve_met: price >= self.auction.reserve_price,
+        });
+
+        // The buyer's bid is fully spent, so their record goes away now
+        self.bid_record.close(self.buyer.to_account_info())
+    }
+}
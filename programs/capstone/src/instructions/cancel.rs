@@ -2,15 +2,18 @@ use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{
-        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
-        TransferChecked,
+        close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
     },
 };
 
+use super::bundle::Bundle;
 use super::error::AuctionError;
-use crate::Auction;
+use super::escrow::EscrowTeardown;
+use super::transfer_hook::transfer_checked;
+use crate::{Auction, AuctionCancelled};
 
-/// Lets the maker reclaim their NFT if the auction ended with zero bids.
+/// Lets the maker reclaim their NFT if the auction ended with zero bids (or,
+/// for sealed-bid auctions, zero valid reveals).
 /// Without this, a no-bid auction would permanently lock the NFT in the vault
 /// because resolve_auction requires a winner_bid_record PDA that was never created.
 #[derive(Accounts)]
@@ -55,7 +58,8 @@ pub struct CancelAuction<'info> {
     #[account(address = auction.nft_mint)]
     pub nft_mint: InterfaceAccount<'info, Mint>,
 
-    #[account(address = auction.bid_mint)]
+    /// Writable so transfer fees withheld in the vault can be harvested to it
+    #[account(mut, address = auction.bid_mint)]
     pub bid_mint: InterfaceAccount<'info, Mint>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -64,21 +68,31 @@ pub struct CancelAuction<'info> {
 }
 
 impl<'info> CancelAuction<'info> {
-    pub fn cancel(&mut self) -> Result<()> {
+    /// `remaining_accounts` holds a `(mint, vault, maker_ata)` triplet per
+    /// extra NFT of a bundle auction, followed by any transfer-hook accounts.
+    pub fn cancel(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
         let clock = Clock::get()?;
 
-        // Can only cancel after the auction period is over
+        // Can only cancel after the auction period (and any reveal window) is over
         require!(
-            clock.unix_timestamp >= self.auction.end_time,
+            clock.unix_timestamp >= self.auction.settle_time(),
             AuctionError::AuctionNotEnded
         );
 
         // Only allow cancellation if nobody bid — if there are bids,
-        // the normal resolve + refund flow should be used instead
-        require!(
-            self.auction.highest_bid_amount == 0,
-            AuctionError::AuctionHasBids
-        );
+        // the normal resolve + refund flow should be used instead. A
+        // multi-winner auction can also be cancelled when no bid on its
+        // leaderboard met the reserve, since nobody can claim a unit.
+        let has_winners = if self.auction.winners > 1 {
+            self.auction.winning_entries() > 0
+        } else {
+            self.auction.highest_bid_amount > 0
+        };
+        require!(!has_winners, AuctionError::AuctionHasBids);
+
+        // Cancelling ends the auction either way. Anything still owed to
+        // bidders refunds through claim_refund from here on.
+        self.auction.resolved = true;
 
         // Build PDA signer seeds for the auction authority
         let seed_bytes = self.auction.seed.to_le_bytes();
@@ -100,8 +114,9 @@ impl<'info> CancelAuction<'info> {
                     authority: self.auction.to_account_info(),
                 },
                 signer_seeds,
-            ),
-            1,
+            )
+            .with_remaining_accounts(remaining_accounts.to_vec()),
+            self.auction.prize_amount,
             self.nft_mint.decimals,
         )?;
 
@@ -116,26 +131,36 @@ impl<'info> CancelAuction<'info> {
             signer_seeds,
         ))?;
 
-        close_account(CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            CloseAccount {
-                account: self.vault_bid.to_account_info(),
-                destination: self.maker.to_account_info(),
-                authority: self.auction.to_account_info(),
-            },
+        // Return the rest of a bundle the same way
+        let maker = self.maker.to_account_info();
+        Bundle {
+            auction: &self.auction.to_account_info(),
+            payer: &maker,
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+            hook_accounts: remaining_accounts,
+        }
+        .release(
+            &self.auction.bundle_mints,
+            remaining_accounts,
+            &maker,
+            &maker,
             signer_seeds,
-        ))?;
-
-        // Manually close the Auction PDA — Anchor's `close` macro can't help
-        // here because we're inside the impl, not in the accounts struct.
-        let auction_info = self.auction.to_account_info();
-        let maker_info = self.maker.to_account_info();
-
-        let rent = auction_info.lamports();
-        **auction_info.lamports.borrow_mut() = 0;
-        **maker_info.lamports.borrow_mut() = maker_info.lamports().checked_add(rent).unwrap();
-        auction_info.data.borrow_mut().fill(0);
+        )?;
 
-        Ok(())
+        emit!(AuctionCancelled {
+            auction: self.auction.key(),
+            maker: self.maker.key(),
+        });
+
+        EscrowTeardown {
+            auction: &self.auction,
+            maker: &self.maker.to_account_info(),
+            vault_bid: &self.vault_bid,
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+        }
+        .close_if_empty(signer_seeds)
     }
 }
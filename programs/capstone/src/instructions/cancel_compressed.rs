@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use super::bubblegum::{Bubblegum, BUBBLEGUM_ID, SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
+use super::error::AuctionError;
+use super::escrow::EscrowTeardown;
+use crate::{Auction, AuctionCancelled};
+
+/// cancel_auction for compressed NFT prizes: hands the leaf back to the maker
+/// when the auction ended without a winning bid.
+#[derive(Accounts)]
+pub struct CancelCompressedAuction<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"auction", maker.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = auction.compressed.is_some() @ AuctionError::NotCompressedAuction,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_bid: InterfaceAccount<'info, TokenAccount>,
+
+    /// Writable so transfer fees withheld in the vault can be harvested to it
+    #[account(mut, address = auction.bid_mint)]
+    pub bid_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Bubblegum's config PDA for the tree, checked by Bubblegum
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the auction's leaf in the handler
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the Bubblegum program
+    #[account(address = BUBBLEGUM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the SPL Noop program Bubblegum logs leaves through
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelCompressedAuction<'info> {
+    /// `proof` works as in `ResolveCompressedAuction::resolve_compressed`.
+    pub fn cancel_compressed(
+        &mut self,
+        root: [u8; 32],
+        proof: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= self.auction.settle_time(),
+            AuctionError::AuctionNotEnded
+        );
+        require!(
+            self.auction.highest_bid_amount == 0,
+            AuctionError::AuctionHasBids
+        );
+
+        let leaf = self
+            .auction
+            .compressed
+            .ok_or(AuctionError::NotCompressedAuction)?;
+        require_keys_eq!(
+            self.merkle_tree.key(),
+            leaf.merkle_tree,
+            AuctionError::InvalidCompressedParams
+        );
+
+        self.auction.resolved = true;
+
+        let seed_bytes = self.auction.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            self.auction.maker.as_ref(),
+            seed_bytes.as_ref(),
+            &[self.auction.bump],
+        ]];
+
+        // Hand the leaf back to the maker
+        let auction_info = self.auction.to_account_info();
+        Bubblegum {
+            tree_config: &self.tree_config.to_account_info(),
+            merkle_tree: &self.merkle_tree.to_account_info(),
+            log_wrapper: &self.log_wrapper.to_account_info(),
+            compression_program: &self.compression_program.to_account_info(),
+            bubblegum_program: &self.bubblegum_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+        }
+        .transfer(
+            &auction_info,
+            &auction_info,
+            &self.maker.to_account_info(),
+            &leaf,
+            root,
+            proof,
+            signer_seeds,
+        )?;
+
+        emit!(AuctionCancelled {
+            auction: self.auction.key(),
+            maker: self.maker.key(),
+        });
+
+        EscrowTeardown {
+            auction: &self.auction,
+            maker: &self.maker.to_account_info(),
+            vault_bid: &self.vault_bid,
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+        }
+        .close_if_empty(signer_seeds)
+    }
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use super::core::{Core, MPL_CORE_ID};
+use super::error::AuctionError;
+use super::escrow::EscrowTeardown;
+use crate::{Auction, AuctionCancelled};
+
+/// cancel_auction for Core asset prizes: hands the asset back to the maker
+/// when the auction ended without a winning bid.
+#[derive(Accounts)]
+pub struct CancelCoreAuction<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"auction", maker.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = auction.core.is_some() @ AuctionError::NotCoreAuction,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_bid: InterfaceAccount<'info, TokenAccount>,
+
+    /// Writable so transfer fees withheld in the vault can be harvested to it
+    #[account(mut, address = auction.bid_mint)]
+    pub bid_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Checked against the auction's asset
+    #[account(mut, address = auction.nft_mint)]
+    pub asset: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the collection recorded at make
+    pub collection: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Pinned to the Core program
+    #[account(address = MPL_CORE_ID)]
+    pub core_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl CancelCoreAuction<'_> {
+    pub fn cancel_core(&mut self) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= self.auction.settle_time(),
+            AuctionError::AuctionNotEnded
+        );
+        require!(
+            self.auction.highest_bid_amount == 0,
+            AuctionError::AuctionHasBids
+        );
+
+        let core = self.auction.core.ok_or(AuctionError::NotCoreAuction)?;
+        require!(
+            self.collection.as_ref().map(|c| c.key()) == core.collection,
+            AuctionError::InvalidCoreParams
+        );
+
+        self.auction.resolved = true;
+
+        let seed_bytes = self.auction.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            self.auction.maker.as_ref(),
+            seed_bytes.as_ref(),
+            &[self.auction.bump],
+        ]];
+
+        // Hand the asset back to the maker
+        let collection = self.collection.as_ref().map(|c| c.to_account_info());
+        Core {
+            asset: &self.asset.to_account_info(),
+            collection: collection.as_ref(),
+            payer: &self.maker.to_account_info(),
+            core_program: &self.core_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+        }
+        .transfer(
+            &self.auction.to_account_info(),
+            &self.maker.to_account_info(),
+            signer_seeds,
+        )?;
+
+        emit!(AuctionCancelled {
+            auction: self.auction.key(),
+            maker: self.maker.key(),
+        });
+
+        EscrowTeardown {
+            auction: &self.auction,
+            maker: &self.maker.to_account_info(),
+            vault_bid: &self.vault_bid,
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+        }
+        .close_if_empty(signer_seeds)
+    }
+}
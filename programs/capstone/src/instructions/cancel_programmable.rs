@@ -0,0 +1,212 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token_interface::{
+    close_account, CloseAccount, Mint, TokenAccount, TokenInterface,
+};
+
+use super::error::AuctionError;
+use super::escrow::EscrowTeardown;
+use super::token_metadata::{ProgrammableTransfer, MPL_TOKEN_AUTH_RULES_ID};
+use crate::{Auction, AuctionCancelled};
+
+/// cancel_auction for programmable NFT prizes: hands the NFT back to the
+/// maker through Token Metadata when the auction ended without a winning bid.
+#[derive(Accounts)]
+pub struct CancelProgrammableAuction<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"auction", maker.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = auction.programmable @ AuctionError::NotProgrammableAuction,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_nft: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: The maker's NFT ATA, created if needed by Token Metadata
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &maker.key(),
+            &nft_mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub maker_nft_ata: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = bid_token_program,
+    )]
+    pub vault_bid: InterfaceAccount<'info, TokenAccount>,
+
+    /// Writable so transfer fees withheld in the vault can be harvested to it
+    #[account(mut, address = auction.bid_mint, mint::token_program = bid_token_program)]
+    pub bid_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Token record of vault_nft, closed by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            Metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+            b"token_record",
+            vault_nft.key().as_ref(),
+        ],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub vault_token_record: UncheckedAccount<'info>,
+
+    /// CHECK: Token record of maker_nft_ata, created by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            Metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+            b"token_record",
+            maker_nft_ata.key().as_ref(),
+        ],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub maker_token_record: UncheckedAccount<'info>,
+
+    #[account(address = auction.nft_mint)]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Pinned to the NFT's metadata PDA, checked by Token Metadata
+    #[account(
+        mut,
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref()],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub nft_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the NFT's master edition PDA
+    #[account(
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref(), b"edition"],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub nft_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the Token Auth Rules program. Only needed when the
+    /// NFT has a rule set.
+    #[account(address = MPL_TOKEN_AUTH_RULES_ID)]
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: The NFT's rule set, checked against its metadata by Token Metadata
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// CHECK: Pinned to the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    /// The NFT's token program
+    pub token_program: Interface<'info, TokenInterface>,
+    /// The bid mint's token program, which may be Token-2022
+    pub bid_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl CancelProgrammableAuction<'_> {
+    pub fn cancel_programmable(&mut self) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= self.auction.settle_time(),
+            AuctionError::AuctionNotEnded
+        );
+        require!(
+            self.auction.highest_bid_amount == 0,
+            AuctionError::AuctionHasBids
+        );
+
+        self.auction.resolved = true;
+
+        let seed_bytes = self.auction.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            self.auction.maker.as_ref(),
+            seed_bytes.as_ref(),
+            &[self.auction.bump],
+        ]];
+
+        // Hand the NFT back to the maker, then close the emptied vault
+        // unless Token Metadata already did
+        let authorization_rules_program = self
+            .authorization_rules_program
+            .as_ref()
+            .map(|a| a.to_account_info());
+        let authorization_rules = self
+            .authorization_rules
+            .as_ref()
+            .map(|a| a.to_account_info());
+        ProgrammableTransfer {
+            nft_mint: &self.nft_mint.to_account_info(),
+            nft_metadata: &self.nft_metadata.to_account_info(),
+            nft_edition: &self.nft_edition.to_account_info(),
+            authorization_rules_program: authorization_rules_program.as_ref(),
+            authorization_rules: authorization_rules.as_ref(),
+            payer: &self.maker.to_account_info(),
+            token_metadata_program: &self.token_metadata_program.to_account_info(),
+            sysvar_instructions: &self.sysvar_instructions.to_account_info(),
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+        }
+        .transfer(
+            &self.vault_nft.to_account_info(),
+            &self.auction.to_account_info(),
+            &self.vault_token_record.to_account_info(),
+            &self.maker_nft_ata.to_account_info(),
+            &self.maker.to_account_info(),
+            &self.maker_token_record.to_account_info(),
+            signer_seeds,
+        )?;
+        if self.vault_nft.to_account_info().lamports() > 0 {
+            close_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                CloseAccount {
+                    account: self.vault_nft.to_account_info(),
+                    destination: self.maker.to_account_info(),
+                    authority: self.auction.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        emit!(AuctionCancelled {
+            auction: self.auction.key(),
+            maker: self.maker.key(),
+        });
+
+        EscrowTeardown {
+            auction: &self.auction,
+            maker: &self.maker.to_account_info(),
+            vault_bid: &self.vault_bid,
+            bid_mint: &self.bid_mint,
+            token_program: &self.bid_token_program,
+        }
+        .close_if_empty(signer_seeds)
+    }
+}
@@ -0,0 +1,215 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use super::error::AuctionError;
+use super::escrow::EscrowTeardown;
+use super::resolve::{Royalties, Settlement};
+use crate::{Auction, Bids, Config, PrizeClaimed};
+
+/// Pays out one unit of a multi-winner auction to a bidder on the
+/// leaderboard, who pays their own bid. Permissionless like resolve_auction,
+/// so anyone can crank a winner's claim. The first claim also sends any
+/// unsold units back to the maker.
+#[derive(Accounts)]
+pub struct ClaimPrize<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Checked against the auction leaderboard in the handler
+    #[account(mut)]
+    pub winner: AccountInfo<'info>,
+
+    /// CHECK: We only need this to validate the maker ATAs
+    #[account(mut, address = auction.maker)]
+    pub maker: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub auction: Box<Account<'info, Auction>>,
+
+    /// Closed on claim, which also stops the same winner claiming twice
+    #[account(
+        mut,
+        seeds = [b"bids", auction.key().as_ref(), winner.key().as_ref()],
+        bump = winner_bid_record.bump,
+    )]
+    pub winner_bid_record: Account<'info, Bids>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = bid_mint,
+        associated_token::authority = maker,
+    )]
+    pub maker_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Validates the fee_recipient_bid_ata ownership, and receives the
+    /// fee directly on native-SOL auctions
+    #[account(mut, address = config.fee_recipient)]
+    pub fee_recipient: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = bid_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_recipient_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = winner,
+    )]
+    pub winner_nft_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the units nobody won
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = maker,
+    )]
+    pub maker_nft_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_nft: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_bid: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = auction.nft_mint)]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Writable so transfer fees withheld in the vault can be harvested to it
+    #[account(mut, address = auction.bid_mint)]
+    pub bid_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Pinned to the NFT's metadata PDA. Royalties are only paid when it
+    /// holds Token Metadata data, so NFTs minted without metadata still settle.
+    #[account(
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref()],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub nft_metadata: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimPrize<'info> {
+    /// `remaining_accounts` works as in `ResolveAuction::resolve`, minus the
+    /// bundle accounts: creator accounts first, then any transfer-hook accounts.
+    pub fn claim_prize(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let clock = Clock::get()?;
+
+        require!(
+            self.auction.winners > 1,
+            AuctionError::NotMultiWinnerAuction
+        );
+        require!(
+            clock.unix_timestamp >= self.auction.end_time,
+            AuctionError::AuctionNotEnded
+        );
+        require!(
+            self.auction.is_winner(&self.winner.key()),
+            AuctionError::NotAWinner
+        );
+
+        // The first claim settles the lot: units without a winning bid go
+        // back to the maker, and losing bidders can start refunding
+        let first_claim = !self.auction.resolved;
+        self.auction.resolved = true;
+        self.auction.escrowed -= self.winner_bid_record.amount;
+
+        let seed_bytes = self.auction.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            self.auction.maker.as_ref(),
+            seed_bytes.as_ref(),
+            &[self.auction.bump],
+        ]];
+
+        let settlement = Settlement {
+            auction: &self.auction,
+            maker: &self.maker,
+            maker_bid_ata: &self.maker_bid_ata,
+            fee_recipient: &self.fee_recipient,
+            fee_recipient_bid_ata: &self.fee_recipient_bid_ata,
+            vault_nft: Some(&self.vault_nft),
+            vault_bid: &self.vault_bid,
+            nft_mint: Some(&self.nft_mint),
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+            royalties: Royalties::from_metadata(&self.nft_metadata)?,
+            creator_atas: remaining_accounts,
+            hook_accounts: remaining_accounts,
+        };
+
+        let mut units_left = self.vault_nft.amount;
+        if first_claim {
+            let unsold = self.auction.prize_amount - self.auction.winning_entries();
+            if unsold > 0 {
+                settlement.send_prize(
+                    self.maker_nft_ata.to_account_info(),
+                    unsold,
+                    signer_seeds,
+                )?;
+                units_left -= unsold;
+            }
+        }
+
+        settlement.send_prize(self.winner_nft_ata.to_account_info(), 1, signer_seeds)?;
+        units_left -= 1;
+        let amount = self.winner_bid_record.amount;
+        let (fee, royalties) = settlement.pay_out(amount, signer_seeds)?;
+
+        let lot_settled = units_left == 0;
+        if lot_settled {
+            settlement.close_prize_vault(signer_seeds)?;
+        }
+
+        emit!(PrizeClaimed {
+            auction: self.auction.key(),
+            winner: self.winner.key(),
+            amount,
+            fee,
+            royalties,
+        });
+
+        // Winner paid rent for this PDA when they bid — give it back to them
+        self.winner_bid_record
+            .close(self.winner.to_account_info())?;
+
+        // If every loser has already refunded, this last claim tears down the
+        // bid vault and the Auction PDA like the final claim_refund would
+        self.vault_bid.reload()?;
+        if lot_settled {
+            EscrowTeardown {
+                auction: &self.auction,
+                maker: &self.maker,
+                vault_bid: &self.vault_bid,
+                bid_mint: &self.bid_mint,
+                token_program: &self.token_program,
+            }
+            .close_if_empty(signer_seeds)?;
+        }
+
+        Ok(())
+    }
+}
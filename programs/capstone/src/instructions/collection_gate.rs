@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token_interface::TokenAccount;
+
+use super::error::AuctionError;
+use crate::Auction;
+
+/// On a holders-only auction, checks that `bidder` holds an NFT whose
+/// metadata is a verified member of `auction.required_collection`. The bidder
+/// presents the NFT's token account and its Token Metadata account.
+pub(crate) fn verify_holder(
+    auction: &Auction,
+    bidder: &Pubkey,
+    nft_account: Option<&InterfaceAccount<TokenAccount>>,
+    nft_metadata: Option<&UncheckedAccount>,
+) -> Result<()> {
+    let Some(required_collection) = auction.required_collection else {
+        return Ok(());
+    };
+    let (Some(nft_account), Some(nft_metadata)) = (nft_account, nft_metadata) else {
+        return err!(AuctionError::MissingCollectionProof);
+    };
+    require!(
+        nft_account.owner == *bidder && nft_account.amount > 0,
+        AuctionError::NotCollectionHolder
+    );
+
+    let (metadata_key, _) = Pubkey::find_program_address(
+        &[
+            b"metadata",
+            Metadata::id().as_ref(),
+            nft_account.mint.as_ref(),
+        ],
+        &Metadata::id(),
+    );
+    require!(
+        nft_metadata.key() == metadata_key && nft_metadata.owner == &Metadata::id(),
+        AuctionError::NotCollectionHolder
+    );
+    let metadata = MetadataAccount::try_deserialize(&mut &nft_metadata.try_borrow_data()?[..])?;
+    require!(
+        metadata
+            .collection
+            .as_ref()
+            .is_some_and(|c| c.verified && c.key == required_collection),
+        AuctionError::NotCollectionHolder
+    );
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use super::collection_gate::verify_holder;
+use super::error::AuctionError;
+use super::escrow::BidDeposit;
+use crate::{Auction, AuctionKind, BidCommitted, Bids};
+
+/// Sealed-bid phase one: lock a deposit in the vault alongside a hash of the
+/// real bid. The deposit must cover the bid, but the bid itself stays hidden
+/// until reveal_bid.
+#[derive(Accounts)]
+pub struct CommitBid<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    #[account(mut)]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        space = Bids::DISCRIMINATOR.len() + Bids::INIT_SPACE,
+        seeds = [b"bids", auction.key().as_ref(), bidder.key().as_ref()],
+        bump
+    )]
+    pub bid_record: Account<'info, Bids>,
+
+    /// Not needed on native-SOL auctions, where lamports come from `bidder`
+    #[account(mut)]
+    pub bidder_bid_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_bid: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = auction.bid_mint)]
+    pub bid_mint: InterfaceAccount<'info, Mint>,
+
+    /// Holders-only auctions: a token account of the bidder's holding an NFT
+    /// from the required collection
+    pub holder_nft_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: Holders-only auctions: that NFT's metadata, checked in the handler
+    pub holder_nft_metadata: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CommitBid<'info> {
+    /// `proof` places the bidder on the auction's allowlist, if it has one.
+    /// `hook_accounts` are forwarded to the bid mint's transfer hook, if any.
+    pub fn commit_bid(
+        &mut self,
+        commitment: [u8; 32],
+        deposit: u64,
+        proof: &[[u8; 32]],
+        hook_accounts: &[AccountInfo<'info>],
+        bumps: &CommitBidBumps,
+    ) -> Result<()> {
+        require!(
+            matches!(self.auction.kind, AuctionKind::Sealed { .. }),
+            AuctionError::NotSealedAuction
+        );
+
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= self.auction.start_time,
+            AuctionError::AuctionNotStarted
+        );
+        require!(
+            clock.unix_timestamp < self.auction.end_time,
+            AuctionError::AuctionEnded
+        );
+
+        require!(deposit > 0, AuctionError::ZeroBid);
+        verify_holder(
+            &self.auction,
+            &self.bidder.key(),
+            self.holder_nft_account.as_deref(),
+            self.holder_nft_metadata.as_ref(),
+        )?;
+        require!(
+            self.auction.allows(&self.bidder.key(), proof),
+            AuctionError::NotOnAllowlist
+        );
+        // An all-zero commitment is what marks a record as uncommitted, so
+        // accepting one would let the bidder commit again over their deposit
+        require!(commitment != [0; 32], AuctionError::InvalidCommitment);
+        require!(
+            self.bid_record.commitment == [0; 32],
+            AuctionError::AlreadyCommitted
+        );
+
+        // Only what reaches the vault backs the commitment
+        let deposit = BidDeposit {
+            auction: &self.auction,
+            bidder: &self.bidder,
+            bidder_bid_ata: self.bidder_bid_ata.as_ref(),
+            vault_bid: &self.vault_bid,
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+            system_program: &self.system_program,
+            hook_accounts,
+        }
+        .collect(deposit)?;
+
+        self.bid_record.set_inner(Bids {
+            bidder: self.bidder.key(),
+            amount: deposit,
+            bump: bumps.bid_record,
+            refunded: false,
+            commitment,
+            revealed: false,
+            mint: self.bid_mint.key(),
+        });
+
+        self.auction.escrowed = self
+            .auction
+            .escrowed
+            .checked_add(deposit)
+            .ok_or(AuctionError::EscrowOverflow)?;
+
+        emit!(BidCommitted {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            deposit,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+use super::error::AuctionError;
+use super::resolve::Royalties;
+
+pub const MPL_CORE_ID: Pubkey = pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+
+/// Index of `TransferV1` in Core's instruction enum
+const TRANSFER_V1_DISCRIMINATOR: u8 = 14;
+
+/// Core's account `Key`s, the first byte of every account it owns
+const ASSET_V1: u8 = 1;
+const PLUGIN_HEADER_V1: u8 = 3;
+const PLUGIN_REGISTRY_V1: u8 = 4;
+const COLLECTION_V1: u8 = 5;
+
+/// Indices of the plugins read here in Core's `PluginType` enum
+const ROYALTIES: u8 = 0;
+const PERMANENT_FREEZE_DELEGATE: u8 = 5;
+const PERMANENT_TRANSFER_DELEGATE: u8 = 7;
+const PERMANENT_BURN_DELEGATE: u8 = 8;
+
+/// The accounts needed to move a Metaplex Core asset. Shared by the make,
+/// resolve and cancel instructions of Core auctions.
+///
+/// Core takes its optional accounts positionally, with its own program id
+/// standing in for any that are left out.
+pub(crate) struct Core<'a, 'info> {
+    pub asset: &'a AccountInfo<'info>,
+    pub collection: Option<&'a AccountInfo<'info>>,
+    pub payer: &'a AccountInfo<'info>,
+    pub core_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'info> Core<'_, 'info> {
+    /// Moves the asset from its owner `authority`, who signs (directly or
+    /// through `signer_seeds`), to `new_owner`.
+    pub fn transfer(
+        &self,
+        authority: &AccountInfo<'info>,
+        new_owner: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let collection = self.collection.unwrap_or(self.core_program);
+        let accounts = vec![
+            AccountMeta::new(self.asset.key(), false),
+            AccountMeta::new_readonly(collection.key(), false),
+            AccountMeta::new(self.payer.key(), true),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new_readonly(new_owner.key(), false),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+            // No log wrapper: the asset isn't compressed
+            AccountMeta::new_readonly(MPL_CORE_ID, false),
+        ];
+
+        // TransferV1Args { compression_proof: None }
+        let data = vec![TRANSFER_V1_DISCRIMINATOR, 0];
+
+        invoke_signed(
+            &Instruction {
+                program_id: MPL_CORE_ID,
+                accounts,
+                data,
+            },
+            &[
+                self.asset.clone(),
+                collection.clone(),
+                self.payer.clone(),
+                authority.clone(),
+                new_owner.clone(),
+                self.system_program.clone(),
+                self.core_program.clone(),
+            ],
+            signer_seeds,
+        )
+        .map_err(Into::into)
+    }
+}
+
+/// The plugins on a Core asset or collection, read straight from the account:
+/// after the base struct comes a plugin header pointing at the registry,
+/// which lists each plugin's type and offset.
+pub(crate) struct CorePlugins {
+    pub types: Vec<u8>,
+    pub royalties: Option<Royalties>,
+}
+
+impl CorePlugins {
+    pub fn read(account: &AccountInfo) -> Result<Self> {
+        let data = account.try_borrow_data()?;
+        let mut plugins = Self {
+            types: Vec::new(),
+            royalties: None,
+        };
+
+        let mut cursor: &[u8] = &data;
+        match u8::deserialize(&mut cursor)? {
+            ASSET_V1 => {
+                // owner, update authority (None, Address or Collection), name, uri, seq
+                Pubkey::deserialize(&mut cursor)?;
+                if u8::deserialize(&mut cursor)? != 0 {
+                    Pubkey::deserialize(&mut cursor)?;
+                }
+                <(String, String, Option<u64>)>::deserialize(&mut cursor)?;
+            }
+            COLLECTION_V1 => {
+                // update authority, name, uri, num_minted, current_size
+                <(Pubkey, String, String, u32, u32)>::deserialize(&mut cursor)?;
+            }
+            _ => return err!(AuctionError::InvalidCoreParams),
+        }
+        // Without plugins the account ends with its base struct
+        if cursor.is_empty() {
+            return Ok(plugins);
+        }
+
+        let (header_key, registry_offset) = <(u8, u64)>::deserialize(&mut cursor)?;
+        let mut registry = data
+            .get(registry_offset as usize..)
+            .ok_or(AuctionError::InvalidCoreParams)?;
+        require!(
+            header_key == PLUGIN_HEADER_V1 && u8::deserialize(&mut registry)? == PLUGIN_REGISTRY_V1,
+            AuctionError::InvalidCoreParams
+        );
+
+        for _ in 0..u32::deserialize(&mut registry)? {
+            let plugin_type = u8::deserialize(&mut registry)?;
+            // Authority: None, Owner, UpdateAuthority or Address { address }
+            if u8::deserialize(&mut registry)? == 3 {
+                Pubkey::deserialize(&mut registry)?;
+            }
+            let offset = u64::deserialize(&mut registry)?;
+
+            if plugin_type == ROYALTIES {
+                let mut plugin = data
+                    .get(offset as usize..)
+                    .ok_or(AuctionError::InvalidCoreParams)?;
+                // Plugin::Royalties { basis_points, creators, rule_set }, where
+                // each creator is an address and a percentage
+                let (_, basis_points, creators) =
+                    <(u8, u16, Vec<(Pubkey, u8)>)>::deserialize(&mut plugin)?;
+                plugins.royalties = Some(Royalties {
+                    basis_points,
+                    creators,
+                });
+            }
+            plugins.types.push(plugin_type);
+        }
+
+        Ok(plugins)
+    }
+
+    /// Whether a plugin authority could move, freeze or burn the asset at
+    /// any time, whoever owns it.
+    pub fn has_permanent_delegate(&self) -> bool {
+        self.types.iter().any(|plugin_type| {
+            matches!(
+                *plugin_type,
+                PERMANENT_FREEZE_DELEGATE | PERMANENT_TRANSFER_DELEGATE | PERMANENT_BURN_DELEGATE
+            )
+        })
+    }
+}
@@ -16,4 +16,132 @@ pub enum AuctionError {
     AuctionHasBids,
     #[msg("The bid has already been refunded.")]
     AlreadyRefunded,
+    #[msg("The bid amount must be greater than zero.")]
+    ZeroBid,
+    #[msg("The bid is below the auction's starting price.")]
+    BidBelowStartPrice,
+    #[msg("The bid does not beat the highest bid by the minimum increment.")]
+    BidIncrementTooSmall,
+    #[msg("Soft-close settings cannot be negative.")]
+    InvalidSoftClose,
+    #[msg("This auction has no buy-now price.")]
+    BuyNowDisabled,
+    #[msg("The buy-now price must be at least the reserve and starting price, and only English auctions take one.")]
+    InvalidBuyNowPrice,
+    #[msg("The signer is not allowed to perform this action.")]
+    Unauthorized,
+    #[msg("The fee cannot exceed 1000 basis points.")]
+    InvalidFeeBps,
+    #[msg("A token account is missing for one of the NFT's creators.")]
+    MissingCreatorAccount,
+    #[msg("Creator token account does not match the creator or bid mint.")]
+    InvalidCreatorAccount,
+    #[msg("Fees and royalties exceed the sale amount.")]
+    PayoutExceedsProceeds,
+    #[msg("This auction does not accept open bids.")]
+    NotEnglishAuction,
+    #[msg("Dutch auctions need a nonzero start price at or above the floor price, and a floor at or above the reserve price.")]
+    InvalidDutchParams,
+    #[msg("This auction does not take sealed bids.")]
+    NotSealedAuction,
+    #[msg("Sealed auctions need a reveal end after end time and a penalty of at most 10000 bps.")]
+    InvalidSealedParams,
+    #[msg("This bidder has already committed a sealed bid.")]
+    AlreadyCommitted,
+    #[msg("The reveal window has closed.")]
+    RevealWindowClosed,
+    #[msg("This sealed bid has already been revealed.")]
+    AlreadyRevealed,
+    #[msg("The revealed amount and salt do not match the commitment.")]
+    CommitmentMismatch,
+    #[msg("The revealed bid is larger than the escrowed deposit.")]
+    BidExceedsDeposit,
+    #[msg("The maker's bid token account is required to collect the penalty.")]
+    MissingPenaltyAccount,
+    #[msg("Second-price settlement is not available for Dutch auctions.")]
+    InvalidSettlementMode,
+    #[msg("The auction has not started yet.")]
+    AuctionNotStarted,
+    #[msg("The start time must be before the end time.")]
+    InvalidStartTime,
+    #[msg("The end time must be in the future.")]
+    EndTimeInPast,
+    #[msg("The auction runs longer than the maximum allowed duration.")]
+    AuctionTooLong,
+    #[msg("The bid mint must differ from the NFT mint.")]
+    MintsMustDiffer,
+    #[msg("The prize mint must have zero decimals.")]
+    InvalidNftMint,
+    #[msg("At least one prize token must be deposited.")]
+    InvalidDepositAmount,
+    #[msg("An auction must have between 1 and 10 winners.")]
+    InvalidWinnerCount,
+    #[msg("Multi-winner auctions must be English, first-price, and without buy-now.")]
+    InvalidMultiWinnerParams,
+    #[msg("This instruction is only for multi-winner auctions.")]
+    NotMultiWinnerAuction,
+    #[msg("Multi-winner auctions settle through claim_prize.")]
+    MultiWinnerAuction,
+    #[msg("This bidder did not win a unit.")]
+    NotAWinner,
+    #[msg("Bundle accounts must be (mint, vault, token account) triplets matching the auction.")]
+    InvalidBundleAccounts,
+    #[msg("A bundle holds at most 5 extra NFTs.")]
+    BundleTooLarge,
+    #[msg("Each NFT in a bundle must be distinct from the others and from the bid mint.")]
+    DuplicateBundleMint,
+    #[msg("Bundle auctions must have a single winner and settle through resolve_auction.")]
+    InvalidBundleParams,
+    #[msg("SPL auctions need the bidder's bid-mint token account.")]
+    MissingBidTokenAccount,
+    #[msg("Native SOL auctions must use the wrapped SOL mint as bid mint.")]
+    InvalidNativeMint,
+    #[msg("Payment mints must be distinct, have a non-zero rate, and are only for single-winner English SPL auctions without buy-now.")]
+    InvalidPaymentMints,
+    #[msg("This auction does not accept bids in that mint.")]
+    UnsupportedBidMint,
+    #[msg("A bidder must keep bidding in the mint of their first bid.")]
+    BidMintMismatch,
+    #[msg("Compressed NFT auctions must be single-winner English or sealed-bid auctions.")]
+    InvalidCompressedParams,
+    #[msg("This instruction is only for compressed NFT auctions.")]
+    NotCompressedAuction,
+    #[msg("Compressed NFT and Core asset prizes are not held in vault_nft.")]
+    PrizeNotInVault,
+    #[msg("Core asset auctions must be single-winner English or sealed-bid auctions, with the asset's collection if it has one.")]
+    InvalidCoreParams,
+    #[msg("This instruction is only for Core asset auctions.")]
+    NotCoreAuction,
+    #[msg("Programmable NFT auctions must be single-winner English or sealed-bid auctions.")]
+    InvalidProgrammableParams,
+    #[msg("This instruction is only for programmable NFT auctions.")]
+    NotProgrammableAuction,
+    #[msg("Holders-only and allowlisted auctions must take bids and have no buy-now price.")]
+    InvalidBidderGate,
+    #[msg("Holders-only auctions need the bidder's NFT token account and metadata.")]
+    MissingCollectionProof,
+    #[msg("The bidder does not hold an NFT of the required collection.")]
+    NotCollectionHolder,
+    #[msg("The bidder is not on this auction's allowlist.")]
+    NotOnAllowlist,
+    #[msg("The withdrawal penalty can't exceed 10000 basis points.")]
+    InvalidWithdrawPenalty,
+    #[msg("Only a bid that isn't leading can be withdrawn, and not from a second-price auction.")]
+    CannotWithdrawBid,
+    #[msg("Open bids have already reached the buy-now price.")]
+    BuyNowOutbid,
+    #[msg("The royalties passed do not match the compressed NFT's leaf.")]
+    LeafRoyaltiesMismatch,
+    #[msg("Core assets with a permanent transfer, freeze or burn delegate can't be auctioned.")]
+    CorePermanentDelegate,
+    #[msg("The bid is worth nothing once converted into bid_mint units.")]
+    BidRoundsToZero,
+    #[msg("The amount is too large to cover the bid mint's transfer fee.")]
+    TransferFeeOverflow,
+    #[msg("A sealed bid's commitment cannot be all zeroes.")]
+    InvalidCommitment,
+    #[msg("The auction's escrow total would overflow.")]
+    EscrowOverflow,
+    #[msg("The vault of the auction's bid_mint is required when the winner paid in another mint.")]
+    MissingPrimaryVault,
 }
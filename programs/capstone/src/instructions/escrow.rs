@@ -0,0 +1,244 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
+use anchor_spl::token_2022_extensions::{
+    harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint,
+};
+use anchor_spl::token_interface::{
+    close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use super::error::AuctionError;
+use super::transfer_hook::transfer_checked;
+use crate::Auction;
+
+/// Where a bidder's funds go in: lamports straight from their wallet into the
+/// Auction PDA on a native-SOL auction, otherwise tokens from their bid-mint
+/// account into `vault_bid`. Shared by bid, commit_bid and buy_now.
+pub(crate) struct BidDeposit<'a, 'info> {
+    pub auction: &'a Account<'info, Auction>,
+    pub bidder: &'a Signer<'info>,
+    /// Only needed on SPL auctions
+    pub bidder_bid_ata: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub vault_bid: &'a InterfaceAccount<'info, TokenAccount>,
+    pub bid_mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub system_program: &'a Program<'info, System>,
+    /// Forwarded to a bid mint's transfer hook
+    pub hook_accounts: &'a [AccountInfo<'info>],
+}
+
+impl BidDeposit<'_, '_> {
+    /// Moves `amount` into escrow and returns how much actually arrived,
+    /// which is less than `amount` when a Token-2022 transfer fee is withheld.
+    pub fn collect(&self, amount: u64) -> Result<u64> {
+        if self.auction.native_sol {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.bidder.to_account_info(),
+                        to: self.auction.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+            return Ok(amount);
+        }
+
+        let bidder_bid_ata = self
+            .bidder_bid_ata
+            .ok_or(AuctionError::MissingBidTokenAccount)?;
+        let cpi_ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            TransferChecked {
+                from: bidder_bid_ata.to_account_info(),
+                to: self.vault_bid.to_account_info(),
+                mint: self.bid_mint.to_account_info(),
+                authority: self.bidder.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(self.hook_accounts.to_vec());
+        transfer_checked(cpi_ctx, amount, self.bid_mint.decimals)?;
+
+        // `vault_bid` still holds the balance loaded before the transfer
+        let vault_info = self.vault_bid.to_account_info();
+        let vault = TokenAccount::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+        Ok(vault.amount - self.vault_bid.amount)
+    }
+}
+
+/// Where a bidder's funds come back out: lamports from the Auction PDA on a
+/// native-SOL auction, otherwise tokens from `vault_bid`. Any penalty goes to
+/// the maker. Shared by claim_refund and withdraw_bid.
+pub(crate) struct BidRefund<'a, 'info> {
+    pub auction: &'a Account<'info, Auction>,
+    pub bidder: &'a AccountInfo<'info>,
+    pub maker: &'a AccountInfo<'info>,
+    /// Only needed on SPL auctions
+    pub bidder_bid_ata: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    /// Only needed on SPL auctions when a penalty applies
+    pub maker_bid_ata: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub vault_bid: &'a InterfaceAccount<'info, TokenAccount>,
+    pub bid_mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    /// Forwarded to a bid mint's transfer hook
+    pub hook_accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'info> BidRefund<'_, 'info> {
+    /// Returns `refund_amount` to the bidder and `penalty` to the maker.
+    pub fn release(
+        &self,
+        refund_amount: u64,
+        penalty: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        if self.auction.native_sol {
+            let auction_info = self.auction.to_account_info();
+            release_lamports(&auction_info, self.maker, penalty)?;
+            return release_lamports(&auction_info, self.bidder, refund_amount);
+        }
+
+        if penalty > 0 {
+            let maker_bid_ata = self
+                .maker_bid_ata
+                .ok_or(AuctionError::MissingPenaltyAccount)?;
+            self.pay_from_vault(maker_bid_ata, penalty, signer_seeds)?;
+        }
+        let bidder_bid_ata = self
+            .bidder_bid_ata
+            .ok_or(AuctionError::MissingBidTokenAccount)?;
+        self.pay_from_vault(bidder_bid_ata, refund_amount, signer_seeds)
+    }
+
+    fn pay_from_vault(
+        &self,
+        to: &InterfaceAccount<'info, TokenAccount>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            TransferChecked {
+                from: self.vault_bid.to_account_info(),
+                to: to.to_account_info(),
+                mint: self.bid_mint.to_account_info(),
+                authority: self.auction.to_account_info(),
+            },
+            signer_seeds,
+        )
+        .with_remaining_accounts(self.hook_accounts.to_vec());
+        transfer_checked(cpi_ctx, amount, self.bid_mint.decimals)
+    }
+}
+
+/// What's left of an auction once nothing more is owed out of escrow: the
+/// bid vault and the Auction PDA, both closed with their rent going to the
+/// maker. Shared by the cancel paths, claim_refund and claim_prize.
+pub(crate) struct EscrowTeardown<'a, 'info> {
+    pub auction: &'a Account<'info, Auction>,
+    pub maker: &'a AccountInfo<'info>,
+    pub vault_bid: &'a InterfaceAccount<'info, TokenAccount>,
+    pub bid_mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+}
+
+impl<'info> EscrowTeardown<'_, 'info> {
+    /// Tears everything down once escrow is empty. Sealed bids that were
+    /// never revealed, or multi-winner bids below the reserve, can leave
+    /// deposits behind after a cancel; the vault and the Auction PDA then
+    /// stay around for claim_refund, whose last caller tears them down.
+    pub fn close_if_empty(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        if !self.auction.escrow_empty(self.vault_bid.amount) {
+            return Ok(());
+        }
+        self.close_vault(signer_seeds)?;
+        self.close_auction()
+    }
+
+    /// Closes the emptied `vault_bid`, harvesting any withheld transfer fees
+    /// to the mint first.
+    pub fn close_vault(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        harvest_fees(
+            &self.token_program.to_account_info(),
+            &self.bid_mint.to_account_info(),
+            &self.vault_bid.to_account_info(),
+        )?;
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.vault_bid.to_account_info(),
+                destination: self.maker.clone(),
+                authority: self.auction.to_account_info(),
+            },
+            signer_seeds,
+        ))
+    }
+
+    /// Closes the Auction PDA by hand. Anchor's `close` constraint can't do
+    /// it, since whichever instruction empties the escrow last tears it down.
+    pub fn close_auction(&self) -> Result<()> {
+        let auction_info = self.auction.to_account_info();
+        release_lamports(&auction_info, self.maker, auction_info.lamports())?;
+        auction_info.data.borrow_mut().fill(0);
+        Ok(())
+    }
+}
+
+/// How much has to be sent so that `net` lands after `mint`'s transfer fee.
+pub(crate) fn gross_up(mint: &InterfaceAccount<Mint>, net: u64) -> Result<u64> {
+    let Some(config) = transfer_fee_config(&mint.to_account_info())? else {
+        return Ok(net);
+    };
+    let fee = config
+        .calculate_inverse_epoch_fee(Clock::get()?.epoch, net)
+        .ok_or(AuctionError::TransferFeeOverflow)?;
+    Ok(net
+        .checked_add(fee)
+        .ok_or(AuctionError::TransferFeeOverflow)?)
+}
+
+/// Token-2022 keeps transfer fees withheld in the receiving account, and an
+/// account with withheld fees can't be closed. Sweeps them from `vault_bid`
+/// to the mint, where the fee authority can collect them, before teardown.
+pub(crate) fn harvest_fees<'info>(
+    token_program: &AccountInfo<'info>,
+    bid_mint: &AccountInfo<'info>,
+    vault_bid: &AccountInfo<'info>,
+) -> Result<()> {
+    if transfer_fee_config(bid_mint)?.is_none() {
+        return Ok(());
+    }
+    harvest_withheld_tokens_to_mint(
+        CpiContext::new(
+            token_program.clone(),
+            HarvestWithheldTokensToMint {
+                token_program_id: token_program.clone(),
+                mint: bid_mint.clone(),
+            },
+        ),
+        vec![vault_bid.clone()],
+    )
+}
+
+fn transfer_fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(mint.get_extension::<TransferFeeConfig>().ok().copied())
+}
+
+/// Pays escrowed lamports out of a native-SOL auction. The program owns the
+/// Auction PDA, so it can debit it directly without a system transfer.
+pub(crate) fn release_lamports<'info>(
+    auction: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    auction.sub_lamports(amount)?;
+    to.add_lamports(amount)?;
+    Ok(())
+}
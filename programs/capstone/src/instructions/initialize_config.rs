@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use super::error::AuctionError;
+use crate::program::Capstone;
+use crate::Config;
+
+/// Creates the global fee config. Only the program's upgrade authority can
+/// do this, so nobody can front-run the deployment and claim the admin seat.
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Config::DISCRIMINATOR.len() + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Capstone>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ AuctionError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeConfig<'info> {
+    pub fn initialize_config(
+        &mut self,
+        fee_bps: u16,
+        fee_recipient: Pubkey,
+        bumps: &InitializeConfigBumps,
+    ) -> Result<()> {
+        require!(fee_bps <= Config::MAX_FEE_BPS, AuctionError::InvalidFeeBps);
+
+        self.config.set_inner(Config {
+            admin: self.admin.key(),
+            fee_bps,
+            fee_recipient,
+            bump: bumps.config,
+        });
+
+        Ok(())
+    }
+}
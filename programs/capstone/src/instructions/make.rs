@@ -1,10 +1,17 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+    token::spl_token::native_mint,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::Auction;
+use super::bundle::Bundle;
+use super::error::AuctionError;
+use super::transfer_hook::transfer_checked;
+use crate::{
+    Auction, AuctionCreated, AuctionKind, BidRules, Config, PaymentMint, SettlementMode, SoftClose,
+    MAX_PAYMENT_MINTS, MAX_WINNERS,
+};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -12,10 +19,17 @@ pub struct MakeAuction<'info> {
     #[account(mut)]
     pub maker: Signer<'info>,
 
-    #[account(mint::token_program = token_program)]
+    /// Must be a whole-unit mint: a single NFT or a lot of semi-fungible tokens
+    #[account(
+        mint::token_program = token_program,
+        constraint = nft_mint.decimals == 0 @ AuctionError::InvalidNftMint,
+    )]
     pub nft_mint: InterfaceAccount<'info, Mint>,
 
-    #[account(mint::token_program = token_program)]
+    #[account(
+        mint::token_program = token_program,
+        constraint = bid_mint.key() != nft_mint.key() @ AuctionError::MintsMustDiffer,
+    )]
     pub bid_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
@@ -35,6 +49,10 @@ pub struct MakeAuction<'info> {
     )]
     pub auction: Account<'info, Auction>,
 
+    /// Its fee is copied onto the auction, so later changes don't reach it
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
     #[account(
         init,
         payer = maker,
@@ -60,12 +78,71 @@ pub struct MakeAuction<'info> {
 }
 
 impl<'info> MakeAuction<'info> {
+    #[allow(clippy::too_many_arguments)]
     pub fn init_auction(
         &mut self,
         seed: u64,
         end_time: i64,
+        reserve_price: u64,
+        bid_rules: BidRules,
+        soft_close: SoftClose,
+        buy_now_price: u64,
+        kind: AuctionKind,
+        settlement_mode: SettlementMode,
+        start_time: i64,
+        winners: u8,
+        native_sol: bool,
+        payment_mints: Vec<PaymentMint>,
+        required_collection: Option<Pubkey>,
+        allowlist_root: Option<[u8; 32]>,
         bumps: &MakeAuctionBumps,
     ) -> Result<()> {
+        let start_time = validate_schedule(
+            end_time,
+            start_time,
+            reserve_price,
+            &bid_rules,
+            &soft_close,
+            &kind,
+            settlement_mode,
+        )?;
+        // Dutch and sealed auctions never read buy_now_price, so a stored one
+        // would only mislead
+        require!(
+            buy_now_price == 0
+                || (kind == AuctionKind::English
+                    && buy_now_price >= reserve_price
+                    && buy_now_price >= bid_rules.start_price),
+            AuctionError::InvalidBuyNowPrice
+        );
+        require!(
+            winners >= 1 && winners as usize <= MAX_WINNERS,
+            AuctionError::InvalidWinnerCount
+        );
+        // Each winner pays their own bid through claim_prize, so there is no
+        // single price to buy out or settle at second price
+        require!(
+            winners == 1
+                || (kind == AuctionKind::English
+                    && settlement_mode == SettlementMode::FirstPrice
+                    && buy_now_price == 0),
+            AuctionError::InvalidMultiWinnerParams
+        );
+        // Native SOL auctions still open their bid-side token accounts, so
+        // pin them to the wrapped SOL mint
+        require!(
+            !native_sol || self.bid_mint.key() == native_mint::ID,
+            AuctionError::InvalidNativeMint
+        );
+        self.validate_payment_mints(&payment_mints, &kind, winners, native_sol, buy_now_price)?;
+        // Only bids check the collection and allowlist, so a gated auction
+        // can't be bought outright
+        require!(
+            (required_collection.is_none() && allowlist_root.is_none())
+                || (!matches!(kind, AuctionKind::Dutch { .. }) && buy_now_price == 0),
+            AuctionError::InvalidBidderGate
+        );
+
         self.auction.set_inner(Auction {
             seed,
             maker: self.maker.key(),
@@ -76,12 +153,85 @@ impl<'info> MakeAuction<'info> {
             resolved: false,
             highest_bidder: Pubkey::default(),
             highest_bid_amount: 0,
+            reserve_price,
+            bid_rules,
+            soft_close,
+            extended_by: 0,
+            buy_now_price,
+            kind,
+            second_highest_bid_amount: 0,
+            settlement_mode,
+            start_time,
+            prize_amount: 0,
+            winners,
+            leaderboard: Vec::new(),
+            bundle_mints: Vec::new(),
+            native_sol,
+            escrowed: 0,
+            payment_mints,
+            compressed: None,
+            core: None,
+            programmable: false,
+            required_collection,
+            allowlist_root,
+            fee_bps: self.config.fee_bps,
         });
 
         Ok(())
     }
 
-    pub fn deposit_prize(&mut self, deposit_amount: u64) -> Result<()> {
+    /// Extra payment mints settle through resolve_auction in the winner's
+    /// mint, so they're limited to single-winner English auctions paid in
+    /// SPL tokens without a buy-now price.
+    fn validate_payment_mints(
+        &self,
+        payment_mints: &[PaymentMint],
+        kind: &AuctionKind,
+        winners: u8,
+        native_sol: bool,
+        buy_now_price: u64,
+    ) -> Result<()> {
+        if payment_mints.is_empty() {
+            return Ok(());
+        }
+        require!(
+            payment_mints.len() <= MAX_PAYMENT_MINTS
+                && *kind == AuctionKind::English
+                && winners == 1
+                && !native_sol
+                && buy_now_price == 0,
+            AuctionError::InvalidPaymentMints
+        );
+        for (index, payment) in payment_mints.iter().enumerate() {
+            require!(
+                payment.numerator > 0
+                    && payment.denominator > 0
+                    && payment.mint != self.bid_mint.key()
+                    && payment.mint != self.nft_mint.key()
+                    && !payment_mints[..index]
+                        .iter()
+                        .any(|p| p.mint == payment.mint),
+                AuctionError::InvalidPaymentMints
+            );
+        }
+        Ok(())
+    }
+
+    /// `hook_accounts` are forwarded to the NFT's transfer hook, if any.
+    pub fn deposit_prize(
+        &mut self,
+        deposit_amount: u64,
+        hook_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        // Settlement and cancellation move the whole lot; a multi-winner
+        // auction needs exactly one unit per winner
+        require!(
+            deposit_amount > 0
+                && (self.auction.winners == 1 || deposit_amount == self.auction.winners as u64),
+            AuctionError::InvalidDepositAmount
+        );
+        self.auction.prize_amount = deposit_amount;
+
         let transfer_accounts = TransferChecked {
             from: self.maker_nft_ata.to_account_info(),
             to: self.vault_nft.to_account_info(),
@@ -89,8 +239,140 @@ impl<'info> MakeAuction<'info> {
             authority: self.maker.to_account_info(),
         };
 
-        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts)
+            .with_remaining_accounts(hook_accounts.to_vec());
 
-        transfer_checked(cpi_ctx, deposit_amount, self.nft_mint.decimals)
+        transfer_checked(cpi_ctx, deposit_amount, self.nft_mint.decimals)?;
+
+        Ok(())
     }
+
+    /// Locks the `bundle_size` extra NFTs of a bundle auction, passed as the
+    /// first remaining accounts in `(mint, vault, maker_ata)` triplets. Any
+    /// transfer-hook accounts follow them. A bundle is settled as one lot by
+    /// resolve_auction, so it can't be bought out or split between several
+    /// winners.
+    pub fn deposit_bundle(
+        &mut self,
+        bundle_size: u8,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let bundle_len = bundle_size as usize * Bundle::ACCOUNTS_PER_MINT;
+        require!(
+            remaining_accounts.len() >= bundle_len,
+            AuctionError::InvalidBundleAccounts
+        );
+        let bundle_accounts = &remaining_accounts[..bundle_len];
+
+        if !bundle_accounts.is_empty() {
+            require!(
+                self.auction.winners == 1
+                    && self.auction.buy_now_price == 0
+                    && !matches!(self.auction.kind, AuctionKind::Dutch { .. }),
+                AuctionError::InvalidBundleParams
+            );
+        }
+
+        let maker = self.maker.to_account_info();
+        let bundle = Bundle {
+            auction: &self.auction.to_account_info(),
+            payer: &maker,
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+            hook_accounts: remaining_accounts,
+        };
+        self.auction.bundle_mints = bundle.deposit(
+            &maker,
+            bundle_accounts,
+            &[self.nft_mint.key(), self.bid_mint.key()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Announces the auction once its whole prize is in escrow.
+    pub fn emit_created(&self) {
+        emit!(AuctionCreated {
+            auction: self.auction.key(),
+            maker: self.maker.key(),
+            nft_mint: self.nft_mint.key(),
+            bid_mint: self.bid_mint.key(),
+            prize_amount: self.auction.prize_amount,
+            start_time: self.auction.start_time,
+            end_time: self.auction.end_time,
+            reserve_price: self.auction.reserve_price,
+            buy_now_price: self.auction.buy_now_price,
+            bundle_mints: self.auction.bundle_mints.clone(),
+        });
+    }
+}
+
+/// Checks shared by every kind of auction creation: the bidding window, bid
+/// rules, soft close and the kind's own parameters. Returns the start time, where zero
+/// means now.
+pub(crate) fn validate_schedule(
+    end_time: i64,
+    start_time: i64,
+    reserve_price: u64,
+    bid_rules: &BidRules,
+    soft_close: &SoftClose,
+    kind: &AuctionKind,
+    settlement_mode: SettlementMode,
+) -> Result<i64> {
+    // The auction must end in the future, but not absurdly far out
+    let now = Clock::get()?.unix_timestamp;
+    require!(end_time > now, AuctionError::EndTimeInPast);
+    require!(
+        end_time - now <= Auction::MAX_DURATION,
+        AuctionError::AuctionTooLong
+    );
+
+    // A start time of zero opens the auction immediately
+    let start_time = if start_time == 0 { now } else { start_time };
+    require!(start_time < end_time, AuctionError::InvalidStartTime);
+
+    require!(
+        bid_rules.withdraw_penalty_bps <= 10_000,
+        AuctionError::InvalidWithdrawPenalty
+    );
+    require!(
+        soft_close.window >= 0 && soft_close.extension >= 0 && soft_close.max_extension >= 0,
+        AuctionError::InvalidSoftClose
+    );
+    if let AuctionKind::Dutch {
+        start_price,
+        floor_price,
+        step_interval,
+    } = *kind
+    {
+        // The price must never fall to a level the reserve would refuse,
+        // or a buyer could pay for a sale that doesn't clear it. A listing
+        // priced at zero could never be bought, since buy_now needs a price
+        // above the top bid.
+        require!(
+            start_price > 0
+                && start_price >= floor_price
+                && floor_price >= reserve_price
+                && step_interval >= 0,
+            AuctionError::InvalidDutchParams
+        );
+    }
+    if let AuctionKind::Sealed {
+        reveal_end,
+        unrevealed_penalty_bps,
+    } = *kind
+    {
+        require!(
+            reveal_end > end_time && unrevealed_penalty_bps <= 10_000,
+            AuctionError::InvalidSealedParams
+        );
+    }
+    // A Dutch sale has no runner-up to price against
+    require!(
+        settlement_mode == SettlementMode::FirstPrice || !matches!(kind, AuctionKind::Dutch { .. }),
+        AuctionError::InvalidSettlementMode
+    );
+
+    Ok(start_time)
 }
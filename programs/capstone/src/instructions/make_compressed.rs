@@ -0,0 +1,198 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::bubblegum::{Bubblegum, BUBBLEGUM_ID, SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
+use super::error::AuctionError;
+use super::make::validate_schedule;
+use crate::{
+    Auction, AuctionCreated, AuctionKind, BidRules, CompressedNft, Config, SettlementMode,
+    SoftClose,
+};
+
+/// Lists a compressed NFT. Instead of a `vault_nft`, the leaf itself is
+/// transferred to the Auction PDA inside its Bubblegum tree, and
+/// resolve_compressed_auction or cancel_compressed_auction moves it on.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeCompressedAuction<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// CHECK: The leaf's current delegate (usually the maker), which is part
+    /// of the leaf hash Bubblegum verifies
+    pub leaf_delegate: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub bid_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"auction", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        space = Auction::DISCRIMINATOR.len() + Auction::INIT_SPACE,
+        bump,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Its fee is copied onto the auction, so later changes don't reach it
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_bid: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Bubblegum's config PDA for the tree, checked by Bubblegum
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: The concurrent Merkle tree holding the leaf, checked by the
+    /// compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the Bubblegum program
+    #[account(address = BUBBLEGUM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the SPL Noop program Bubblegum logs leaves through
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> MakeCompressedAuction<'info> {
+    /// `proof` is the leaf's Merkle proof against `root`, passed as
+    /// remaining accounts.
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_compressed_auction(
+        &mut self,
+        seed: u64,
+        end_time: i64,
+        reserve_price: u64,
+        bid_rules: BidRules,
+        soft_close: SoftClose,
+        kind: AuctionKind,
+        settlement_mode: SettlementMode,
+        start_time: i64,
+        leaf: CompressedNft,
+        root: [u8; 32],
+        required_collection: Option<Pubkey>,
+        allowlist_root: Option<[u8; 32]>,
+        proof: &[AccountInfo<'info>],
+        bumps: &MakeCompressedAuctionBumps,
+    ) -> Result<()> {
+        let start_time = validate_schedule(
+            end_time,
+            start_time,
+            reserve_price,
+            &bid_rules,
+            &soft_close,
+            &kind,
+            settlement_mode,
+        )?;
+        // The leaf can only move as a whole through resolve or cancel, so
+        // there's no buy-now, Dutch sale or lot to split between winners
+        require!(
+            matches!(kind, AuctionKind::English | AuctionKind::Sealed { .. }),
+            AuctionError::InvalidCompressedParams
+        );
+        require_keys_eq!(
+            self.merkle_tree.key(),
+            leaf.merkle_tree,
+            AuctionError::InvalidCompressedParams
+        );
+
+        // Indexers know a compressed NFT by its asset id
+        let (asset_id, _) = Pubkey::find_program_address(
+            &[
+                b"asset",
+                leaf.merkle_tree.as_ref(),
+                leaf.nonce.to_le_bytes().as_ref(),
+            ],
+            &BUBBLEGUM_ID,
+        );
+
+        self.auction.set_inner(Auction {
+            seed,
+            maker: self.maker.key(),
+            nft_mint: asset_id,
+            bid_mint: self.bid_mint.key(),
+            end_time,
+            bump: bumps.auction,
+            resolved: false,
+            highest_bidder: Pubkey::default(),
+            highest_bid_amount: 0,
+            reserve_price,
+            bid_rules,
+            soft_close,
+            extended_by: 0,
+            buy_now_price: 0,
+            kind,
+            second_highest_bid_amount: 0,
+            settlement_mode,
+            start_time,
+            prize_amount: 1,
+            winners: 1,
+            leaderboard: Vec::new(),
+            bundle_mints: Vec::new(),
+            native_sol: false,
+            escrowed: 0,
+            payment_mints: Vec::new(),
+            compressed: Some(leaf),
+            core: None,
+            programmable: false,
+            required_collection,
+            allowlist_root,
+            fee_bps: self.config.fee_bps,
+        });
+
+        // Take custody: the Auction PDA becomes the leaf's owner and delegate
+        Bubblegum {
+            tree_config: &self.tree_config.to_account_info(),
+            merkle_tree: &self.merkle_tree.to_account_info(),
+            log_wrapper: &self.log_wrapper.to_account_info(),
+            compression_program: &self.compression_program.to_account_info(),
+            bubblegum_program: &self.bubblegum_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+        }
+        .transfer(
+            &self.maker.to_account_info(),
+            &self.leaf_delegate.to_account_info(),
+            &self.auction.to_account_info(),
+            &leaf,
+            root,
+            proof,
+            &[],
+        )?;
+
+        emit!(AuctionCreated {
+            auction: self.auction.key(),
+            maker: self.maker.key(),
+            nft_mint: asset_id,
+            bid_mint: self.bid_mint.key(),
+            prize_amount: 1,
+            start_time,
+            end_time,
+            reserve_price,
+            buy_now_price: 0,
+            bundle_mints: Vec::new(),
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::core::{Core, CorePlugins, MPL_CORE_ID};
+use super::error::AuctionError;
+use super::make::validate_schedule;
+use crate::{
+    Auction, AuctionCreated, AuctionKind, BidRules, Config, CoreAsset, SettlementMode, SoftClose,
+};
+
+/// Lists a Metaplex Core asset. Core assets aren't SPL mints, so instead of a
+/// `vault_nft` the asset itself is transferred to the Auction PDA, and
+/// resolve_core_auction or cancel_core_auction moves it on.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeCoreAuction<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// CHECK: Owned by Core, which checks the maker owns it on transfer
+    #[account(mut, owner = MPL_CORE_ID)]
+    pub asset: UncheckedAccount<'info>,
+
+    /// CHECK: The asset's collection, if it has one, checked by Core
+    #[account(owner = MPL_CORE_ID)]
+    pub collection: Option<UncheckedAccount<'info>>,
+
+    #[account(mint::token_program = token_program)]
+    pub bid_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"auction", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        space = Auction::DISCRIMINATOR.len() + Auction::INIT_SPACE,
+        bump,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Its fee is copied onto the auction, so later changes don't reach it
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_bid: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Pinned to the Core program
+    #[account(address = MPL_CORE_ID)]
+    pub core_program: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl MakeCoreAuction<'_> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_core_auction(
+        &mut self,
+        seed: u64,
+        end_time: i64,
+        reserve_price: u64,
+        bid_rules: BidRules,
+        soft_close: SoftClose,
+        kind: AuctionKind,
+        settlement_mode: SettlementMode,
+        start_time: i64,
+        required_collection: Option<Pubkey>,
+        allowlist_root: Option<[u8; 32]>,
+        bumps: &MakeCoreAuctionBumps,
+    ) -> Result<()> {
+        let start_time = validate_schedule(
+            end_time,
+            start_time,
+            reserve_price,
+            &bid_rules,
+            &soft_close,
+            &kind,
+            settlement_mode,
+        )?;
+        // As with compressed NFTs, the asset only moves as a whole through
+        // resolve or cancel
+        require!(
+            matches!(kind, AuctionKind::English | AuctionKind::Sealed { .. }),
+            AuctionError::InvalidCoreParams
+        );
+        // A permanent delegate, on the asset or inherited from its collection,
+        // could move, freeze or burn the asset while the Auction PDA holds it
+        // and leave every bid stuck
+        let collection = self.collection.as_ref().map(|c| c.to_account_info());
+        for account in [&self.asset.to_account_info()]
+            .into_iter()
+            .chain(collection.as_ref())
+        {
+            require!(
+                !CorePlugins::read(account)?.has_permanent_delegate(),
+                AuctionError::CorePermanentDelegate
+            );
+        }
+
+        let asset = self.asset.key();
+        self.auction.set_inner(Auction {
+            seed,
+            maker: self.maker.key(),
+            nft_mint: asset,
+            bid_mint: self.bid_mint.key(),
+            end_time,
+            bump: bumps.auction,
+            resolved: false,
+            highest_bidder: Pubkey::default(),
+            highest_bid_amount: 0,
+            reserve_price,
+            bid_rules,
+            soft_close,
+            extended_by: 0,
+            buy_now_price: 0,
+            kind,
+            second_highest_bid_amount: 0,
+            settlement_mode,
+            start_time,
+            prize_amount: 1,
+            winners: 1,
+            leaderboard: Vec::new(),
+            bundle_mints: Vec::new(),
+            native_sol: false,
+            escrowed: 0,
+            payment_mints: Vec::new(),
+            compressed: None,
+            core: Some(CoreAsset {
+                collection: self.collection.as_ref().map(|c| c.key()),
+            }),
+            programmable: false,
+            required_collection,
+            allowlist_root,
+            fee_bps: self.config.fee_bps,
+        });
+
+        // Take custody: the Auction PDA becomes the asset's owner
+        Core {
+            asset: &self.asset.to_account_info(),
+            collection: collection.as_ref(),
+            payer: &self.maker.to_account_info(),
+            core_program: &self.core_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+        }
+        .transfer(
+            &self.maker.to_account_info(),
+            &self.auction.to_account_info(),
+            &[],
+        )?;
+
+        emit!(AuctionCreated {
+            auction: self.auction.key(),
+            maker: self.maker.key(),
+            nft_mint: asset,
+            bid_mint: self.bid_mint.key(),
+            prize_amount: 1,
+            start_time,
+            end_time,
+            reserve_price,
+            buy_now_price: 0,
+            bundle_mints: Vec::new(),
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    metadata::Metadata,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::error::AuctionError;
+use super::make::validate_schedule;
+use super::token_metadata::{ProgrammableTransfer, MPL_TOKEN_AUTH_RULES_ID};
+use crate::{Auction, AuctionCreated, AuctionKind, BidRules, Config, SettlementMode, SoftClose};
+
+/// Lists a programmable NFT. The NFT still sits in `vault_nft`, but gets
+/// there (and leaves again through resolve_programmable_auction or
+/// cancel_programmable_auction) through Token Metadata, which checks the
+/// NFT's rule set on every move.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeProgrammableAuction<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+        constraint = nft_mint.decimals == 0 @ AuctionError::InvalidNftMint,
+    )]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = bid_token_program,
+        constraint = bid_mint.key() != nft_mint.key() @ AuctionError::MintsMustDiffer,
+    )]
+    pub bid_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_nft_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"auction", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        space = Auction::DISCRIMINATOR.len() + Auction::INIT_SPACE,
+        bump,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    /// Its fee is copied onto the auction, so later changes don't reach it
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: The Auction PDA's NFT ATA, created by Token Metadata on transfer
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &auction.key(),
+            &nft_mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub vault_nft: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = bid_token_program,
+    )]
+    pub vault_bid: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Pinned to the NFT's metadata PDA, checked by Token Metadata
+    #[account(
+        mut,
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref()],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub nft_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the NFT's master edition PDA
+    #[account(
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref(), b"edition"],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub nft_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Token record of maker_nft_ata, closed by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            Metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+            b"token_record",
+            maker_nft_ata.key().as_ref(),
+        ],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub maker_token_record: UncheckedAccount<'info>,
+
+    /// CHECK: Token record of vault_nft, created by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            Metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+            b"token_record",
+            vault_nft.key().as_ref(),
+        ],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub vault_token_record: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the Token Auth Rules program. Only needed when the
+    /// NFT has a rule set.
+    #[account(address = MPL_TOKEN_AUTH_RULES_ID)]
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: The NFT's rule set, checked against its metadata by Token Metadata
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// CHECK: Pinned to the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    /// The NFT's token program. pNFTs always live under SPL Token.
+    pub token_program: Interface<'info, TokenInterface>,
+    /// The bid mint's token program, which may be Token-2022
+    pub bid_token_program: Interface<'info, TokenInterface>,
+}
+
+impl MakeProgrammableAuction<'_> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_programmable_auction(
+        &mut self,
+        seed: u64,
+        end_time: i64,
+        reserve_price: u64,
+        bid_rules: BidRules,
+        soft_close: SoftClose,
+        kind: AuctionKind,
+        settlement_mode: SettlementMode,
+        start_time: i64,
+        required_collection: Option<Pubkey>,
+        allowlist_root: Option<[u8; 32]>,
+        bumps: &MakeProgrammableAuctionBumps,
+    ) -> Result<()> {
+        let start_time = validate_schedule(
+            end_time,
+            start_time,
+            reserve_price,
+            &bid_rules,
+            &soft_close,
+            &kind,
+            settlement_mode,
+        )?;
+        // Only resolve and cancel route the prize through Token Metadata
+        require!(
+            matches!(kind, AuctionKind::English | AuctionKind::Sealed { .. }),
+            AuctionError::InvalidProgrammableParams
+        );
+
+        self.auction.set_inner(Auction {
+            seed,
+            maker: self.maker.key(),
+            nft_mint: self.nft_mint.key(),
+            bid_mint: self.bid_mint.key(),
+            end_time,
+            bump: bumps.auction,
+            resolved: false,
+            highest_bidder: Pubkey::default(),
+            highest_bid_amount: 0,
+            reserve_price,
+            bid_rules,
+            soft_close,
+            extended_by: 0,
+            buy_now_price: 0,
+            kind,
+            second_highest_bid_amount: 0,
+            settlement_mode,
+            start_time,
+            prize_amount: 1,
+            winners: 1,
+            leaderboard: Vec::new(),
+            bundle_mints: Vec::new(),
+            native_sol: false,
+            escrowed: 0,
+            payment_mints: Vec::new(),
+            compressed: None,
+            core: None,
+            programmable: true,
+            required_collection,
+            allowlist_root,
+            fee_bps: self.config.fee_bps,
+        });
+
+        let authorization_rules_program = self
+            .authorization_rules_program
+            .as_ref()
+            .map(|a| a.to_account_info());
+        let authorization_rules = self
+            .authorization_rules
+            .as_ref()
+            .map(|a| a.to_account_info());
+        ProgrammableTransfer {
+            nft_mint: &self.nft_mint.to_account_info(),
+            nft_metadata: &self.nft_metadata.to_account_info(),
+            nft_edition: &self.nft_edition.to_account_info(),
+            authorization_rules_program: authorization_rules_program.as_ref(),
+            authorization_rules: authorization_rules.as_ref(),
+            payer: &self.maker.to_account_info(),
+            token_metadata_program: &self.token_metadata_program.to_account_info(),
+            sysvar_instructions: &self.sysvar_instructions.to_account_info(),
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+        }
+        .transfer(
+            &self.maker_nft_ata.to_account_info(),
+            &self.maker.to_account_info(),
+            &self.maker_token_record.to_account_info(),
+            &self.vault_nft.to_account_info(),
+            &self.auction.to_account_info(),
+            &self.vault_token_record.to_account_info(),
+            &[],
+        )?;
+
+        emit!(AuctionCreated {
+            auction: self.auction.key(),
+            maker: self.maker.key(),
+            nft_mint: self.nft_mint.key(),
+            bid_mint: self.bid_mint.key(),
+            prize_amount: 1,
+            start_time,
+            end_time,
+            reserve_price,
+            buy_now_price: 0,
+            bundle_mints: Vec::new(),
+        });
+
+        Ok(())
+    }
+}
@@ -1,13 +1,52 @@
 pub mod bid;
+pub mod bubblegum;
+pub mod bundle;
+pub mod buy_now;
 pub mod cancel;
+pub mod cancel_compressed;
+pub mod cancel_core;
+pub mod cancel_programmable;
+pub mod claim_prize;
+pub mod collection_gate;
+pub mod commit_bid;
+pub mod core;
 pub mod error;
+pub mod escrow;
+pub mod initialize_config;
 pub mod make;
+pub mod make_compressed;
+pub mod make_core;
+pub mod make_programmable;
 pub mod refund;
 pub mod resolve;
+pub mod resolve_compressed;
+pub mod resolve_core;
+pub mod resolve_programmable;
+pub mod reveal_bid;
+pub mod token_metadata;
+pub mod transfer_hook;
+pub mod update_config;
+pub mod withdraw_bid;
 
 pub use bid::*;
+pub use buy_now::*;
 pub use cancel::*;
+pub use cancel_compressed::*;
+pub use cancel_core::*;
+pub use cancel_programmable::*;
+pub use claim_prize::*;
+pub use commit_bid::*;
 pub use error::*;
+pub use initialize_config::*;
 pub use make::*;
+pub use make_compressed::*;
+pub use make_core::*;
+pub use make_programmable::*;
 pub use refund::*;
 pub use resolve::*;
+pub use resolve_compressed::*;
+pub use resolve_core::*;
+pub use resolve_programmable::*;
+pub use reveal_bid::*;
+pub use update_config::*;
+pub use withdraw_bid::*;
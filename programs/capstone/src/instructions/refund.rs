@@ -1,14 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{
-        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
-        TransferChecked,
-    },
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
 use super::error::AuctionError;
-use crate::{Auction, Bids};
+use super::escrow::{BidRefund, EscrowTeardown};
+use crate::{Auction, AuctionKind, Bids, RefundClaimed};
 
 #[derive(Accounts)]
 pub struct ClaimRefund<'info> {
@@ -31,13 +29,14 @@ pub struct ClaimRefund<'info> {
     )]
     pub bid_record: Account<'info, Bids>,
 
+    /// Not needed on native-SOL auctions, where lamports go back to `bidder`
     #[account(
         init_if_needed,
         payer = bidder, // If they somehow closed their ATA
         associated_token::mint = bid_mint,
         associated_token::authority = bidder,
     )]
-    pub bidder_bid_ata: InterfaceAccount<'info, TokenAccount>,
+    pub bidder_bid_ata: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -46,32 +45,58 @@ pub struct ClaimRefund<'info> {
     )]
     pub vault_bid: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(address = auction.bid_mint)]
+    /// The mint this bidder escrowed in. Writable so transfer fees withheld
+    /// in the vault can be harvested to it at teardown.
+    #[account(mut, address = bid_record.mint)]
     pub bid_mint: InterfaceAccount<'info, Mint>,
 
+    /// Collects the penalty on an unrevealed sealed bid. Only required when
+    /// a penalty applies on an SPL auction.
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = maker,
+    )]
+    pub maker_bid_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> ClaimRefund<'info> {
-    pub fn refund_loser(&mut self) -> Result<()> {
+    /// `hook_accounts` are forwarded to the bid mint's transfer hook, if any.
+    pub fn refund_loser(&mut self, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
         let clock = Clock::get()?;
 
-        // Ensuring the auction is over
+        // Ensuring the auction is over (including any reveal window)
         require!(
-            clock.unix_timestamp >= self.auction.end_time,
+            clock.unix_timestamp >= self.auction.settle_time(),
             AuctionError::AuctionNotEnded
         );
 
-        // Ensuring the winner cannot withdraw their locked bid
-        require!(
-            self.bid_record.bidder != self.auction.highest_bidder,
-            AuctionError::CannotRefundWinner
-        );
+        // Ensuring the winner cannot withdraw their locked bid, unless the
+        // auction was resolved without meeting the reserve. On a multi-winner
+        // auction every winner pays through claim_prize, and the rest wait
+        // until the lot has been settled so the last refund can't close the
+        // auction with units still in vault_nft.
+        if self.auction.winners > 1 {
+            require!(self.auction.resolved, AuctionError::AuctionNotResolved);
+            require!(
+                !self.auction.is_winner(&self.bid_record.bidder),
+                AuctionError::CannotRefundWinner
+            );
+        } else if self.bid_record.bidder == self.auction.highest_bidder {
+            require!(self.auction.resolved, AuctionError::AuctionNotResolved);
+            require!(
+                !self.auction.reserve_met(),
+                AuctionError::CannotRefundWinner
+            );
+        }
 
         // Ensuring the bid has not been refunded already
         require!(!self.bid_record.refunded, AuctionError::AlreadyRefunded);
+        self.auction.escrowed -= self.bid_record.amount;
 
         // Preparing the PDA signatures to authorize the vault transfer
         let seed_bytes = self.auction.seed.to_le_bytes();
@@ -82,44 +107,60 @@ impl<'info> ClaimRefund<'info> {
             &[self.auction.bump],
         ]];
 
-        // Transfering the losing amount back to the bidder
-        let transfer_ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            TransferChecked {
-                from: self.vault_bid.to_account_info(),
-                to: self.bidder_bid_ata.to_account_info(),
-                mint: self.bid_mint.to_account_info(),
-                authority: self.auction.to_account_info(),
-            },
-            signer_seeds,
-        );
-        transfer_checked(transfer_ctx, self.bid_record.amount, self.bid_mint.decimals)?;
+        // Sealed bids that were never revealed forfeit a share of the deposit
+        let penalty = match self.auction.kind {
+            AuctionKind::Sealed {
+                unrevealed_penalty_bps,
+                ..
+            } if !self.bid_record.revealed => {
+                (self.bid_record.amount as u128 * unrevealed_penalty_bps as u128 / 10_000) as u64
+            }
+            _ => 0,
+        };
+        let refund_amount = self.bid_record.amount - penalty;
+
+        BidRefund {
+            auction: &self.auction,
+            bidder: &self.bidder.to_account_info(),
+            maker: &self.maker,
+            bidder_bid_ata: self.bidder_bid_ata.as_ref(),
+            maker_bid_ata: self.maker_bid_ata.as_ref(),
+            vault_bid: &self.vault_bid,
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+            hook_accounts,
+        }
+        .release(refund_amount, penalty, signer_seeds)?;
+
+        emit!(RefundClaimed {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            amount: refund_amount,
+            penalty,
+        });
 
-        // Last one out turns off the lights — if all tokens have been withdrawn,
+        // Last one out turns off the lights — if all funds have been withdrawn,
         // we close the vault ATA and the Auction PDA so the maker gets their rent back.
+        // With several payment mints, each vault closes as soon as it empties.
         self.vault_bid.reload()?;
-        if self.vault_bid.amount == 0 {
-            // Close the now-empty token vault
-            close_account(CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                CloseAccount {
-                    account: self.vault_bid.to_account_info(),
-                    destination: self.maker.to_account_info(),
-                    authority: self.auction.to_account_info(),
-                },
-                signer_seeds,
-            ))?;
-
-            // Manually close the Auction PDA — Anchor can't do this for us
-            // because the last refund caller isn't the maker, so we zero the
-            // account's lamports and data ourselves.
-            let auction_info = self.auction.to_account_info();
-            let maker_info = self.maker.to_account_info();
-
-            let rent = auction_info.lamports();
-            **auction_info.lamports.borrow_mut() = 0;
-            **maker_info.lamports.borrow_mut() = maker_info.lamports().checked_add(rent).unwrap();
-            auction_info.data.borrow_mut().fill(0);
+        let escrow_empty = self.auction.escrow_empty(self.vault_bid.amount);
+        let vault_empty = if self.auction.native_sol {
+            escrow_empty
+        } else {
+            self.vault_bid.amount == 0
+        };
+        let teardown = EscrowTeardown {
+            auction: &self.auction,
+            maker: &self.maker,
+            vault_bid: &self.vault_bid,
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+        };
+        if vault_empty {
+            teardown.close_vault(signer_seeds)?;
+        }
+        if escrow_empty {
+            teardown.close_auction()?;
         }
 
         Ok(())
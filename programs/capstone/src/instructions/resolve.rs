@@ -1,12 +1,15 @@
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{Metadata, MetadataAccount};
 use anchor_spl::token_interface::{
-    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
-    TransferChecked,
+    close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
 };
 
+use super::bundle::Bundle;
 use super::error::AuctionError;
-use crate::{Auction, Bids};
+use super::escrow::release_lamports;
+use super::transfer_hook::transfer_checked;
+use crate::{Auction, AuctionResolved, Bids, Config};
 
 #[derive(Accounts)]
 pub struct ResolveAuction<'info> {
@@ -24,9 +27,10 @@ pub struct ResolveAuction<'info> {
     #[account(mut, address = auction.maker)]
     pub maker: AccountInfo<'info>,
 
+    /// Only closed when the reserve is met. If it isn't, the top bidder still
+    /// needs this record to claim their refund.
     #[account(
         mut,
-        close = winner, // Winner paid rent for this PDA when they bid — give it back to them
         seeds = [b"bids", auction.key().as_ref(), winner.key().as_ref()],
         bump = winner_bid_record.bump,
     )]
@@ -40,6 +44,31 @@ pub struct ResolveAuction<'info> {
     )]
     pub maker_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Receives the part of a sealed-bid winner's deposit above their bid
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = bid_mint,
+        associated_token::authority = winner,
+    )]
+    pub winner_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Validates the fee_recipient_bid_ata ownership, and receives the
+    /// fee directly on native-SOL auctions
+    #[account(mut, address = config.fee_recipient)]
+    pub fee_recipient: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = resolver, // The protocol's cut of the sale lands here
+        associated_token::mint = bid_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_recipient_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
     #[account(
         init_if_needed,
         payer = resolver, // The crank pays the rent for the winner's new ATA
@@ -48,6 +77,15 @@ pub struct ResolveAuction<'info> {
     )]
     pub winner_nft_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Receives the NFT back if the reserve price was not met
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = nft_mint,
+        associated_token::authority = maker,
+    )]
+    pub maker_nft_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
     #[account(
         mut,
         associated_token::mint = nft_mint,
@@ -65,29 +103,55 @@ pub struct ResolveAuction<'info> {
     #[account(address = auction.nft_mint)]
     pub nft_mint: InterfaceAccount<'info, Mint>,
 
-    #[account(address = auction.bid_mint)]
+    /// The mint the winner bid in, which the sale settles in
+    #[account(address = winner_bid_record.mint)]
     pub bid_mint: InterfaceAccount<'info, Mint>,
 
+    /// The vault of the auction's own `bid_mint`, needed when the winner paid
+    /// in one of its other payment mints. If nobody bid in `bid_mint` it is
+    /// still empty, and no refund would ever close it.
+    #[account(
+        mut,
+        associated_token::mint = auction.bid_mint,
+        associated_token::authority = auction,
+    )]
+    pub primary_vault_bid: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: Pinned to the NFT's metadata PDA. Royalties are only paid when it
+    /// holds Token Metadata data, so NFTs minted without metadata still settle.
+    #[account(
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref()],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub nft_metadata: UncheckedAccount<'info>,
+
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> ResolveAuction<'info> {
-    pub fn resolve(&mut self) -> Result<()> {
+    /// `remaining_accounts` starts with a `(mint, vault, ata)` triplet per
+    /// extra NFT of a bundle auction, where `ata` belongs to whoever receives
+    /// the prize. After those comes one bid-mint token account per creator
+    /// listed in the NFT's metadata, in the same order, and last any accounts
+    /// the NFT or bid mint's transfer hook needs.
+    pub fn resolve(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
         let clock = Clock::get()?;
 
-        // Ensuring the auction is actually over
+        // Ensuring the auction is actually over (including any reveal window)
         require!(
-            clock.unix_timestamp >= self.auction.end_time,
+            clock.unix_timestamp >= self.auction.settle_time(),
             AuctionError::AuctionNotEnded
         );
 
         // Ensuring it hasn't already been resolved to prevent double-spending
         require!(!self.auction.resolved, AuctionError::AlreadyResolved);
+        require!(self.auction.winners == 1, AuctionError::MultiWinnerAuction);
 
         // Mark as resolved immediately (Checks-Effects-Interactions pattern)
-        self.auction.resolved = true;
+        let reserve_met = self.auction.mark_resolved(self.winner_bid_record.amount);
 
         // Preparing the PDA signatures to authorize the vault transfers
         let seed_bytes = self.auction.seed.to_le_bytes();
@@ -98,45 +162,338 @@ impl<'info> ResolveAuction<'info> {
             &[self.auction.bump],
         ]];
 
-        // Transfer the Prize (NFT) to the Winner
+        // If the top bid fell short of the reserve, the NFT goes back to the
+        // maker and every bidder (winner included) refunds through claim_refund
+        let (nft_destination, prize_owner) = if reserve_met {
+            (
+                self.winner_nft_ata.to_account_info(),
+                self.winner.to_account_info(),
+            )
+        } else {
+            (
+                self.maker_nft_ata.to_account_info(),
+                self.maker.to_account_info(),
+            )
+        };
+        let (bundle_accounts, creator_atas) = remaining_accounts.split_at(
+            remaining_accounts
+                .len()
+                .min(self.auction.bundle_mints.len() * Bundle::ACCOUNTS_PER_MINT),
+        );
+
+        let settlement = Settlement {
+            auction: &self.auction,
+            maker: &self.maker,
+            maker_bid_ata: &self.maker_bid_ata,
+            fee_recipient: &self.fee_recipient,
+            fee_recipient_bid_ata: &self.fee_recipient_bid_ata,
+            vault_nft: Some(&self.vault_nft),
+            vault_bid: &self.vault_bid,
+            nft_mint: Some(&self.nft_mint),
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+            royalties: Royalties::from_metadata(&self.nft_metadata)?,
+            creator_atas,
+            hook_accounts: remaining_accounts,
+        };
+
+        // Transfer the Prize (NFT, plus any bundle) to the Winner, or back to the Maker
+        settlement.release_prize(nft_destination, signer_seeds)?;
+        Bundle {
+            auction: &self.auction.to_account_info(),
+            payer: &self.resolver.to_account_info(),
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+            hook_accounts: remaining_accounts,
+        }
+        .release(
+            &self.auction.bundle_mints,
+            bundle_accounts,
+            &prize_owner,
+            &self.maker,
+            signer_seeds,
+        )?;
+
+        let price = self
+            .auction
+            .price_in(&self.bid_mint.key(), self.winner_bid_record.amount);
+        settlement.settle_winner(
+            price,
+            &self.winner,
+            &self.winner_bid_ata,
+            &self.winner_bid_record,
+            signer_seeds,
+        )?;
+
+        if self.bid_mint.key() != self.auction.bid_mint {
+            let primary_vault_bid = self
+                .primary_vault_bid
+                .as_ref()
+                .ok_or(AuctionError::MissingPrimaryVault)?;
+            if primary_vault_bid.amount == 0 {
+                close_account(CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    CloseAccount {
+                        account: primary_vault_bid.to_account_info(),
+                        destination: self.maker.to_account_info(),
+                        authority: self.auction.to_account_info(),
+                    },
+                    signer_seeds,
+                ))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A sale's royalty terms: the seller fee in basis points, split between
+/// the creators by their percentage shares.
+pub(crate) struct Royalties {
+    pub basis_points: u16,
+    pub creators: Vec<(Pubkey, u8)>,
+}
+
+impl Royalties {
+    /// The terms in an NFT's Token Metadata account. NFTs minted without
+    /// metadata or creators pay none.
+    pub fn from_metadata(nft_metadata: &AccountInfo) -> Result<Option<Self>> {
+        if nft_metadata.owner != &Metadata::id() || nft_metadata.data_is_empty() {
+            return Ok(None);
+        }
+        let metadata = MetadataAccount::try_deserialize(&mut &nft_metadata.try_borrow_data()?[..])?;
+        Ok(metadata.creators.as_ref().map(|creators| Self {
+            basis_points: metadata.seller_fee_basis_points,
+            creators: creators
+                .iter()
+                .map(|creator| (creator.address, creator.share))
+                .collect(),
+        }))
+    }
+}
+
+/// The accounts a settlement moves the prize and proceeds through. Shared by
+/// every path that ends an auction with a sale (resolve, buy-now, claim_prize).
+pub(crate) struct Settlement<'a, 'info> {
+    pub auction: &'a Account<'info, Auction>,
+    pub maker: &'a AccountInfo<'info>,
+    pub maker_bid_ata: &'a InterfaceAccount<'info, TokenAccount>,
+    pub fee_recipient: &'a AccountInfo<'info>,
+    pub fee_recipient_bid_ata: &'a InterfaceAccount<'info, TokenAccount>,
+    /// None for compressed NFT prizes, which move through Bubblegum
+    pub vault_nft: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub vault_bid: &'a InterfaceAccount<'info, TokenAccount>,
+    pub nft_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    pub bid_mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    /// None when the prize pays no royalties
+    pub royalties: Option<Royalties>,
+    pub creator_atas: &'a [AccountInfo<'info>],
+    /// Searched by address for the accounts a transfer hook needs
+    pub hook_accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'info> Settlement<'_, 'info> {
+    /// Moves the whole prize lot out of `vault_nft` to `destination`, then closes the
+    /// now-empty vault with its rent going back to the maker.
+    pub fn release_prize(
+        &self,
+        destination: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        self.send_prize(destination, self.auction.prize_amount, signer_seeds)?;
+        self.close_prize_vault(signer_seeds)
+    }
+
+    /// Moves `amount` prize tokens out of `vault_nft` to `destination`.
+    pub fn send_prize(
+        &self,
+        destination: AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let (vault_nft, nft_mint) = self
+            .vault_nft
+            .zip(self.nft_mint)
+            .ok_or(AuctionError::PrizeNotInVault)?;
         let transfer_nft_ctx = CpiContext::new_with_signer(
             self.token_program.to_account_info(),
             TransferChecked {
-                from: self.vault_nft.to_account_info(),
-                to: self.winner_nft_ata.to_account_info(),
-                mint: self.nft_mint.to_account_info(),
+                from: vault_nft.to_account_info(),
+                to: destination,
+                mint: nft_mint.to_account_info(),
                 authority: self.auction.to_account_info(),
             },
             signer_seeds,
-        );
-        transfer_checked(transfer_nft_ctx, 1, self.nft_mint.decimals)?;
+        )
+        .with_remaining_accounts(self.hook_accounts.to_vec());
+        transfer_checked(transfer_nft_ctx, amount, nft_mint.decimals)
+    }
 
-        // Close the now-empty vault_nft ATA — rent goes back to maker
+    /// Closes the empty `vault_nft`, sending its rent back to the maker.
+    pub fn close_prize_vault(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let vault_nft = self.vault_nft.ok_or(AuctionError::PrizeNotInVault)?;
         close_account(CpiContext::new_with_signer(
             self.token_program.to_account_info(),
             CloseAccount {
-                account: self.vault_nft.to_account_info(),
+                account: vault_nft.to_account_info(),
                 destination: self.maker.to_account_info(),
                 authority: self.auction.to_account_info(),
             },
             signer_seeds,
-        ))?;
+        ))
+    }
+
+    /// Finishes a single-winner sale once the prize has moved. Above the
+    /// reserve this pays out `price`, returns whatever the winner escrowed
+    /// beyond it and closes their bid record. Below it nothing is paid, and
+    /// every bidder (winner included) refunds through claim_refund.
+    pub fn settle_winner(
+        &self,
+        price: u64,
+        winner: &AccountInfo<'info>,
+        winner_bid_ata: &InterfaceAccount<'info, TokenAccount>,
+        winner_bid_record: &Account<'info, Bids>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let reserve_met = self.auction.reserve_met();
+        let (mut fee, mut royalties) = (0, 0);
+        if reserve_met {
+            // Transfering the Winning Bid (USDC/Tokens) to the Maker, minus
+            // the protocol fee and creator royalties
+            (fee, royalties) = self.pay_out(price, signer_seeds)?;
+
+            // The winner escrowed more than they pay if this was a sealed
+            // deposit or a second-price auction — return the rest
+            let surplus = winner_bid_record.amount - price;
+            if surplus > 0 {
+                self.pay(winner, winner_bid_ata, surplus, signer_seeds)?;
+            }
+
+            // Winner paid rent for this PDA when they bid — give it back to them
+            winner_bid_record.close(winner.clone())?;
+        }
 
-        // Transfering the Winning Bid (USDC/Tokens) to the Maker
+        emit!(AuctionResolved {
+            auction: self.auction.key(),
+            winner: self.auction.highest_bidder,
+            amount: if reserve_met { price } else { 0 },
+            mint: self.bid_mint.key(),
+            fee,
+            royalties,
+            reserve_met,
+        });
+
+        Ok(())
+    }
+
+    /// Pays the sale proceeds out of escrow: creator royalties first,
+    /// then the protocol fee, then the remainder to the maker. Both cuts are
+    /// taken from the gross amount, but royalties are capped at what the fee
+    /// leaves, so a seller fee near 100% can't make settlement fail.
+    /// Returns `(fee, royalties)`.
+    pub fn pay_out(&self, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<(u64, u64)> {
+        let fee = self.auction.fee_for(amount);
+        let royalties = self.pay_royalties(amount, amount - fee, signer_seeds)?;
+
+        if fee > 0 {
+            self.pay(
+                self.fee_recipient,
+                self.fee_recipient_bid_ata,
+                fee,
+                signer_seeds,
+            )?;
+        }
+
+        let maker_amount = amount
+            .checked_sub(fee)
+            .and_then(|rest| rest.checked_sub(royalties))
+            .ok_or(AuctionError::PayoutExceedsProceeds)?;
+        self.pay(self.maker, self.maker_bid_ata, maker_amount, signer_seeds)?;
+
+        Ok((fee, royalties))
+    }
+
+    /// Splits the seller fee, up to `cap`, between the creators by share. On
+    /// native-SOL auctions `creator_atas` holds the creator wallets instead.
+    fn pay_royalties(&self, amount: u64, cap: u64, signer_seeds: &[&[&[u8]]]) -> Result<u64> {
+        let Some(royalties) = self.royalties.as_ref() else {
+            return Ok(0);
+        };
+
+        let royalty_pool =
+            ((amount as u128 * royalties.basis_points as u128 / 10_000) as u64).min(cap);
+        let mut paid: u64 = 0;
+
+        for (index, &(creator, share)) in royalties.creators.iter().enumerate() {
+            let share = (royalty_pool as u128 * share as u128 / 100) as u64;
+            if share == 0 {
+                continue;
+            }
+
+            let creator_ata = self
+                .creator_atas
+                .get(index)
+                .ok_or(AuctionError::MissingCreatorAccount)?;
+            if self.auction.native_sol {
+                require_keys_eq!(
+                    creator_ata.key(),
+                    creator,
+                    AuctionError::InvalidCreatorAccount
+                );
+                release_lamports(&self.auction.to_account_info(), creator_ata, share)?;
+                paid += share;
+                continue;
+            }
+            let token_account =
+                TokenAccount::try_deserialize(&mut &creator_ata.try_borrow_data()?[..])?;
+            require!(
+                token_account.owner == creator && token_account.mint == self.bid_mint.key(),
+                AuctionError::InvalidCreatorAccount
+            );
+
+            self.pay_from_vault(creator_ata.clone(), share, signer_seeds)?;
+            paid += share;
+        }
+
+        Ok(paid)
+    }
+
+    /// Pays `amount` out of escrow: lamports to `wallet` on a native-SOL
+    /// auction, otherwise bid tokens to `ata`.
+    pub fn pay(
+        &self,
+        wallet: &AccountInfo<'info>,
+        ata: &InterfaceAccount<'info, TokenAccount>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        if self.auction.native_sol {
+            release_lamports(&self.auction.to_account_info(), wallet, amount)
+        } else {
+            self.pay_from_vault(ata.to_account_info(), amount, signer_seeds)
+        }
+    }
+
+    /// Transfers `amount` out of `vault_bid`, signed by the auction PDA.
+    pub fn pay_from_vault(
+        &self,
+        to: AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
         let transfer_bid_ctx = CpiContext::new_with_signer(
             self.token_program.to_account_info(),
             TransferChecked {
                 from: self.vault_bid.to_account_info(),
-                to: self.maker_bid_ata.to_account_info(),
+                to,
                 mint: self.bid_mint.to_account_info(),
                 authority: self.auction.to_account_info(),
             },
             signer_seeds,
-        );
-        transfer_checked(
-            transfer_bid_ctx,
-            self.auction.highest_bid_amount,
-            self.bid_mint.decimals,
         )
+        .with_remaining_accounts(self.hook_accounts.to_vec());
+        transfer_checked(transfer_bid_ctx, amount, self.bid_mint.decimals)
     }
 }
@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use super::bubblegum::{Bubblegum, BUBBLEGUM_ID, SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
+use super::error::AuctionError;
+use super::resolve::{Royalties, Settlement};
+use crate::{Auction, Bids, Config, LeafRoyalties};
+
+/// resolve_auction for compressed NFT prizes: the leaf moves from the Auction
+/// PDA to the winner (or back to the maker) inside its tree, and the
+/// proceeds settle exactly as for an SPL prize. Permissionless.
+#[derive(Accounts)]
+pub struct ResolveCompressedAuction<'info> {
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = auction.compressed.is_some() @ AuctionError::NotCompressedAuction,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    /// CHECK: Receives the leaf and validates the winner_bid_ata ownership
+    #[account(mut, address = auction.highest_bidder)]
+    pub winner: AccountInfo<'info>,
+
+    /// CHECK: We only need this to validate the maker_bid_ata ownership
+    #[account(mut, address = auction.maker)]
+    pub maker: AccountInfo<'info>,
+
+    /// Only closed when the reserve is met, as in resolve_auction
+    #[account(
+        mut,
+        seeds = [b"bids", auction.key().as_ref(), winner.key().as_ref()],
+        bump = winner_bid_record.bump,
+    )]
+    pub winner_bid_record: Account<'info, Bids>,
+
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = bid_mint,
+        associated_token::authority = maker,
+    )]
+    pub maker_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the part of a sealed-bid winner's deposit above their bid
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = bid_mint,
+        associated_token::authority = winner,
+    )]
+    pub winner_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Validates the fee_recipient_bid_ata ownership
+    #[account(mut, address = config.fee_recipient)]
+    pub fee_recipient: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = bid_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_recipient_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_bid: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = auction.bid_mint)]
+    pub bid_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Bubblegum's config PDA for the tree, checked by Bubblegum
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the auction's leaf in the handler
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the Bubblegum program
+    #[account(address = BUBBLEGUM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the SPL Noop program Bubblegum logs leaves through
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ResolveCompressedAuction<'info> {
+    /// `remaining_accounts` starts with the `proof_len` nodes of the
+    /// Auction-owned leaf's Merkle proof against the tree's current `root`.
+    /// After those comes one bid-mint token account per creator in
+    /// `royalties`, in the same order, and last any accounts the bid mint's
+    /// transfer hook needs. `royalties` must be the terms the leaf was minted
+    /// with, so the creators are paid as for any other NFT.
+    pub fn resolve_compressed(
+        &mut self,
+        root: [u8; 32],
+        proof_len: u8,
+        royalties: LeafRoyalties,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= self.auction.settle_time(),
+            AuctionError::AuctionNotEnded
+        );
+        require!(!self.auction.resolved, AuctionError::AlreadyResolved);
+
+        let leaf = self
+            .auction
+            .compressed
+            .ok_or(AuctionError::NotCompressedAuction)?;
+        require_keys_eq!(
+            self.merkle_tree.key(),
+            leaf.merkle_tree,
+            AuctionError::InvalidCompressedParams
+        );
+        require!(
+            leaf.matches(&royalties),
+            AuctionError::LeafRoyaltiesMismatch
+        );
+
+        let reserve_met = self.auction.mark_resolved(self.winner_bid_record.amount);
+
+        let seed_bytes = self.auction.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            self.auction.maker.as_ref(),
+            seed_bytes.as_ref(),
+            &[self.auction.bump],
+        ]];
+
+        // Below the reserve the leaf goes back to the maker and every bidder
+        // refunds through claim_refund
+        let new_owner = if reserve_met {
+            self.winner.to_account_info()
+        } else {
+            self.maker.to_account_info()
+        };
+        let (proof, creator_atas) =
+            remaining_accounts.split_at(remaining_accounts.len().min(proof_len as usize));
+        let auction_info = self.auction.to_account_info();
+        Bubblegum {
+            tree_config: &self.tree_config.to_account_info(),
+            merkle_tree: &self.merkle_tree.to_account_info(),
+            log_wrapper: &self.log_wrapper.to_account_info(),
+            compression_program: &self.compression_program.to_account_info(),
+            bubblegum_program: &self.bubblegum_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+        }
+        .transfer(
+            &auction_info,
+            &auction_info,
+            &new_owner,
+            &leaf,
+            root,
+            proof,
+            signer_seeds,
+        )?;
+
+        let settlement = Settlement {
+            auction: &self.auction,
+            maker: &self.maker,
+            maker_bid_ata: &self.maker_bid_ata,
+            fee_recipient: &self.fee_recipient,
+            fee_recipient_bid_ata: &self.fee_recipient_bid_ata,
+            vault_nft: None,
+            vault_bid: &self.vault_bid,
+            nft_mint: None,
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+            royalties: Some(Royalties {
+                basis_points: royalties.seller_fee_basis_points,
+                creators: royalties
+                    .creators
+                    .iter()
+                    .map(|creator| (creator.address, creator.share))
+                    .collect(),
+            }),
+            creator_atas,
+            hook_accounts: creator_atas,
+        };
+
+        settlement.settle_winner(
+            self.auction.clearing_price(),
+            &self.winner,
+            &self.winner_bid_ata,
+            &self.winner_bid_record,
+            signer_seeds,
+        )
+    }
+}
@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use super::core::{Core, CorePlugins, MPL_CORE_ID};
+use super::error::AuctionError;
+use super::resolve::Settlement;
+use crate::{Auction, Bids, Config};
+
+/// resolve_auction for Core asset prizes: the asset moves from the Auction
+/// PDA to the winner (or back to the maker), and the proceeds settle exactly
+/// as for an SPL prize. Permissionless.
+#[derive(Accounts)]
+pub struct ResolveCoreAuction<'info> {
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = auction.core.is_some() @ AuctionError::NotCoreAuction,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    /// CHECK: Receives the asset and validates the winner_bid_ata ownership
+    #[account(mut, address = auction.highest_bidder)]
+    pub winner: AccountInfo<'info>,
+
+    /// CHECK: We only need this to validate the maker_bid_ata ownership
+    #[account(mut, address = auction.maker)]
+    pub maker: AccountInfo<'info>,
+
+    /// Only closed when the reserve is met, as in resolve_auction
+    #[account(
+        mut,
+        seeds = [b"bids", auction.key().as_ref(), winner.key().as_ref()],
+        bump = winner_bid_record.bump,
+    )]
+    pub winner_bid_record: Account<'info, Bids>,
+
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = bid_mint,
+        associated_token::authority = maker,
+    )]
+    pub maker_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the part of a sealed-bid winner's deposit above their bid
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = bid_mint,
+        associated_token::authority = winner,
+    )]
+    pub winner_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Validates the fee_recipient_bid_ata ownership
+    #[account(mut, address = config.fee_recipient)]
+    pub fee_recipient: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = bid_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_recipient_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_bid: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = auction.bid_mint)]
+    pub bid_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Checked against the auction's asset
+    #[account(mut, address = auction.nft_mint)]
+    pub asset: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the collection recorded at make
+    pub collection: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Pinned to the Core program
+    #[account(address = MPL_CORE_ID)]
+    pub core_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ResolveCoreAuction<'info> {
+    /// Royalties come from the asset's Royalties plugin, or its collection's
+    /// if the asset has none. `remaining_accounts` holds one bid-mint token
+    /// account per creator in that plugin, in the same order, then any
+    /// accounts the bid mint's transfer hook needs.
+    pub fn resolve_core(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= self.auction.settle_time(),
+            AuctionError::AuctionNotEnded
+        );
+        require!(!self.auction.resolved, AuctionError::AlreadyResolved);
+
+        let core = self.auction.core.ok_or(AuctionError::NotCoreAuction)?;
+        require!(
+            self.collection.as_ref().map(|c| c.key()) == core.collection,
+            AuctionError::InvalidCoreParams
+        );
+
+        let reserve_met = self.auction.mark_resolved(self.winner_bid_record.amount);
+
+        let seed_bytes = self.auction.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            self.auction.maker.as_ref(),
+            seed_bytes.as_ref(),
+            &[self.auction.bump],
+        ]];
+
+        // Below the reserve the asset goes back to the maker and every bidder
+        // refunds through claim_refund
+        let new_owner = if reserve_met {
+            self.winner.to_account_info()
+        } else {
+            self.maker.to_account_info()
+        };
+        let collection = self.collection.as_ref().map(|c| c.to_account_info());
+        let royalties = match CorePlugins::read(&self.asset)?.royalties {
+            Some(royalties) => Some(royalties),
+            None => match collection.as_ref() {
+                Some(collection) => CorePlugins::read(collection)?.royalties,
+                None => None,
+            },
+        };
+        Core {
+            asset: &self.asset.to_account_info(),
+            collection: collection.as_ref(),
+            payer: &self.resolver.to_account_info(),
+            core_program: &self.core_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+        }
+        .transfer(&self.auction.to_account_info(), &new_owner, signer_seeds)?;
+
+        let settlement = Settlement {
+            auction: &self.auction,
+            maker: &self.maker,
+            maker_bid_ata: &self.maker_bid_ata,
+            fee_recipient: &self.fee_recipient,
+            fee_recipient_bid_ata: &self.fee_recipient_bid_ata,
+            vault_nft: None,
+            vault_bid: &self.vault_bid,
+            nft_mint: None,
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+            royalties,
+            creator_atas: remaining_accounts,
+            hook_accounts: remaining_accounts,
+        };
+
+        settlement.settle_winner(
+            self.auction.clearing_price(),
+            &self.winner,
+            &self.winner_bid_ata,
+            &self.winner_bid_record,
+            signer_seeds,
+        )
+    }
+}
@@ -0,0 +1,276 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token_interface::{
+    close_account, CloseAccount, Mint, TokenAccount, TokenInterface,
+};
+
+use super::error::AuctionError;
+use super::resolve::{Royalties, Settlement};
+use super::token_metadata::{ProgrammableTransfer, MPL_TOKEN_AUTH_RULES_ID};
+use crate::{Auction, Bids, Config};
+
+/// resolve_auction for programmable NFT prizes: the NFT moves out of
+/// `vault_nft` through Token Metadata, and the proceeds (royalties included)
+/// settle exactly as for any other NFT. Permissionless.
+#[derive(Accounts)]
+pub struct ResolveProgrammableAuction<'info> {
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = auction.programmable @ AuctionError::NotProgrammableAuction,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    /// CHECK: Receives the NFT and validates the winner_bid_ata ownership
+    #[account(mut, address = auction.highest_bidder)]
+    pub winner: AccountInfo<'info>,
+
+    /// CHECK: We only need this to validate the maker_bid_ata ownership
+    #[account(mut, address = auction.maker)]
+    pub maker: AccountInfo<'info>,
+
+    /// Only closed when the reserve is met, as in resolve_auction
+    #[account(
+        mut,
+        seeds = [b"bids", auction.key().as_ref(), winner.key().as_ref()],
+        bump = winner_bid_record.bump,
+    )]
+    pub winner_bid_record: Account<'info, Bids>,
+
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = bid_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = bid_token_program,
+    )]
+    pub maker_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the part of a sealed-bid winner's deposit above their bid
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = bid_mint,
+        associated_token::authority = winner,
+        associated_token::token_program = bid_token_program,
+    )]
+    pub winner_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Validates the fee_recipient_bid_ata ownership
+    #[account(mut, address = config.fee_recipient)]
+    pub fee_recipient: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = bid_mint,
+        associated_token::authority = fee_recipient,
+        associated_token::token_program = bid_token_program,
+    )]
+    pub fee_recipient_bid_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = bid_token_program,
+    )]
+    pub vault_bid: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = auction.bid_mint, mint::token_program = bid_token_program)]
+    pub bid_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_nft: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: The winner's NFT ATA, or the maker's if the reserve wasn't met.
+    /// Created if needed and checked against its owner by Token Metadata.
+    #[account(mut)]
+    pub prize_receiver_ata: UncheckedAccount<'info>,
+
+    /// CHECK: Token record of vault_nft, closed by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            Metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+            b"token_record",
+            vault_nft.key().as_ref(),
+        ],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub vault_token_record: UncheckedAccount<'info>,
+
+    /// CHECK: Token record of prize_receiver_ata, created by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            Metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+            b"token_record",
+            prize_receiver_ata.key().as_ref(),
+        ],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub prize_receiver_token_record: UncheckedAccount<'info>,
+
+    #[account(address = auction.nft_mint)]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Pinned to the NFT's metadata PDA. Also read for royalties.
+    #[account(
+        mut,
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref()],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub nft_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the NFT's master edition PDA
+    #[account(
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref(), b"edition"],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub nft_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the Token Auth Rules program. Only needed when the
+    /// NFT has a rule set.
+    #[account(address = MPL_TOKEN_AUTH_RULES_ID)]
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: The NFT's rule set, checked against its metadata by Token Metadata
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// CHECK: Pinned to the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    /// The NFT's token program
+    pub token_program: Interface<'info, TokenInterface>,
+    /// The bid mint's token program, which may be Token-2022
+    pub bid_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ResolveProgrammableAuction<'info> {
+    /// `remaining_accounts` holds one bid-mint token account per creator
+    /// listed in the NFT's metadata, in the same order, followed by any
+    /// accounts the bid mint's transfer hook needs.
+    pub fn resolve_programmable(
+        &mut self,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= self.auction.settle_time(),
+            AuctionError::AuctionNotEnded
+        );
+        require!(!self.auction.resolved, AuctionError::AlreadyResolved);
+
+        let reserve_met = self.auction.mark_resolved(self.winner_bid_record.amount);
+
+        let seed_bytes = self.auction.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            self.auction.maker.as_ref(),
+            seed_bytes.as_ref(),
+            &[self.auction.bump],
+        ]];
+
+        // Below the reserve the NFT goes back to the maker and every bidder
+        // refunds through claim_refund
+        let new_owner = if reserve_met {
+            self.winner.to_account_info()
+        } else {
+            self.maker.to_account_info()
+        };
+        let authorization_rules_program = self
+            .authorization_rules_program
+            .as_ref()
+            .map(|a| a.to_account_info());
+        let authorization_rules = self
+            .authorization_rules
+            .as_ref()
+            .map(|a| a.to_account_info());
+        ProgrammableTransfer {
+            nft_mint: &self.nft_mint.to_account_info(),
+            nft_metadata: &self.nft_metadata.to_account_info(),
+            nft_edition: &self.nft_edition.to_account_info(),
+            authorization_rules_program: authorization_rules_program.as_ref(),
+            authorization_rules: authorization_rules.as_ref(),
+            payer: &self.resolver.to_account_info(),
+            token_metadata_program: &self.token_metadata_program.to_account_info(),
+            sysvar_instructions: &self.sysvar_instructions.to_account_info(),
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+        }
+        .transfer(
+            &self.vault_nft.to_account_info(),
+            &self.auction.to_account_info(),
+            &self.vault_token_record.to_account_info(),
+            &self.prize_receiver_ata.to_account_info(),
+            &new_owner,
+            &self.prize_receiver_token_record.to_account_info(),
+            signer_seeds,
+        )?;
+
+        // Close the emptied vault unless Token Metadata already did
+        if self.vault_nft.to_account_info().lamports() > 0 {
+            close_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                CloseAccount {
+                    account: self.vault_nft.to_account_info(),
+                    destination: self.maker.to_account_info(),
+                    authority: self.auction.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        // The prize has already left through Token Metadata, so the
+        // settlement only moves bid tokens, under the bid mint's program
+        let settlement = Settlement {
+            auction: &self.auction,
+            maker: &self.maker,
+            maker_bid_ata: &self.maker_bid_ata,
+            fee_recipient: &self.fee_recipient,
+            fee_recipient_bid_ata: &self.fee_recipient_bid_ata,
+            vault_nft: None,
+            vault_bid: &self.vault_bid,
+            nft_mint: None,
+            bid_mint: &self.bid_mint,
+            token_program: &self.bid_token_program,
+            royalties: Royalties::from_metadata(&self.nft_metadata)?,
+            creator_atas: remaining_accounts,
+            hook_accounts: remaining_accounts,
+        };
+
+        settlement.settle_winner(
+            self.auction.clearing_price(),
+            &self.winner,
+            &self.winner_bid_ata,
+            &self.winner_bid_record,
+            signer_seeds,
+        )
+    }
+}
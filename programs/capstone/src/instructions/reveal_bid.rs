@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use super::error::AuctionError;
+use crate::{Auction, AuctionKind, BidRevealed, Bids};
+
+/// Sealed-bid phase two: open the commitment during the reveal window. The
+/// highest valid reveal becomes the leader that resolve_auction settles.
+#[derive(Accounts)]
+pub struct RevealBid<'info> {
+    pub bidder: Signer<'info>,
+
+    #[account(mut)]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        mut,
+        seeds = [b"bids", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid_record.bump,
+        has_one = bidder,
+    )]
+    pub bid_record: Account<'info, Bids>,
+}
+
+impl<'info> RevealBid<'info> {
+    pub fn reveal_bid(&mut self, amount: u64, salt: [u8; 32]) -> Result<()> {
+        let AuctionKind::Sealed { reveal_end, .. } = self.auction.kind else {
+            return err!(AuctionError::NotSealedAuction);
+        };
+
+        // Reveals open once commits close and run until reveal_end
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= self.auction.end_time,
+            AuctionError::AuctionNotEnded
+        );
+        require!(
+            clock.unix_timestamp < reveal_end,
+            AuctionError::RevealWindowClosed
+        );
+
+        require!(!self.bid_record.revealed, AuctionError::AlreadyRevealed);
+        require!(
+            Bids::commitment_for(&self.bidder.key(), amount, &salt) == self.bid_record.commitment,
+            AuctionError::CommitmentMismatch
+        );
+        require!(
+            amount <= self.bid_record.amount,
+            AuctionError::BidExceedsDeposit
+        );
+
+        self.bid_record.revealed = true;
+
+        // Reveals below the opening price count as revealed (no penalty) but
+        // can never win. Ties go to whoever revealed first.
+        let valid = amount > 0 && amount >= self.auction.bid_rules.start_price;
+        let leader_changed = valid && amount > self.auction.highest_bid_amount;
+        if leader_changed {
+            self.auction.take_lead(self.bidder.key(), amount);
+        } else if valid && amount > self.auction.second_highest_bid_amount {
+            self.auction.second_highest_bid_amount = amount;
+        }
+
+        emit!(BidRevealed {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            amount,
+            leader_changed,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata::instructions::{
+    TransferV1Cpi, TransferV1CpiAccounts, TransferV1InstructionArgs,
+};
+
+pub const MPL_TOKEN_AUTH_RULES_ID: Pubkey = pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+
+/// The accounts needed to move a programmable NFT through Token Metadata's
+/// `TransferV1`. Shared by the make, resolve and cancel instructions of pNFT
+/// auctions.
+///
+/// pNFT token accounts stay frozen, so a plain `transfer_checked` can't move
+/// them. Token Metadata thaws the source, moves the token, freezes the
+/// destination and keeps a token record per token account, enforcing the
+/// NFT's rule set if it has one.
+pub(crate) struct ProgrammableTransfer<'a, 'info> {
+    pub nft_mint: &'a AccountInfo<'info>,
+    pub nft_metadata: &'a AccountInfo<'info>,
+    pub nft_edition: &'a AccountInfo<'info>,
+    pub authorization_rules_program: Option<&'a AccountInfo<'info>>,
+    pub authorization_rules: Option<&'a AccountInfo<'info>>,
+    pub payer: &'a AccountInfo<'info>,
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub sysvar_instructions: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'info> ProgrammableTransfer<'_, 'info> {
+    /// Moves the NFT from `from`, owned by `owner` who signs (directly or
+    /// through `signer_seeds`), to `to`, the ATA of `new_owner`. Token Metadata
+    /// creates `to` and its token record if they don't exist yet.
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer(
+        &self,
+        from: &AccountInfo<'info>,
+        owner: &AccountInfo<'info>,
+        from_record: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+        new_owner: &AccountInfo<'info>,
+        to_record: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        TransferV1Cpi::new(
+            self.token_metadata_program,
+            TransferV1CpiAccounts {
+                token: from,
+                token_owner: owner,
+                destination_token: to,
+                destination_owner: new_owner,
+                mint: self.nft_mint,
+                metadata: self.nft_metadata,
+                edition: Some(self.nft_edition),
+                token_record: Some(from_record),
+                destination_token_record: Some(to_record),
+                authority: owner,
+                payer: self.payer,
+                system_program: self.system_program,
+                sysvar_instructions: self.sysvar_instructions,
+                spl_token_program: self.token_program,
+                spl_ata_program: self.associated_token_program,
+                authorization_rules_program: self.authorization_rules_program,
+                authorization_rules: self.authorization_rules,
+            },
+            TransferV1InstructionArgs {
+                amount: 1,
+                authorization_data: None,
+            },
+        )
+        .invoke_signed(signer_seeds)
+        .map_err(Into::into)
+    }
+}
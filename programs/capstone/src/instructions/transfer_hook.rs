@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::onchain::invoke_transfer_checked;
+use anchor_spl::token_interface::TransferChecked;
+
+/// Drop-in for `token_interface::transfer_checked` that can also move
+/// Token-2022 mints with the transfer-hook extension. The hook program, its
+/// extra-account-metas PDA and any accounts the hook asks for are looked up
+/// by address in `ctx.remaining_accounts`, so callers can forward their whole
+/// remaining-accounts slice. Mints without a hook ignore them.
+pub(crate) fn transfer_checked<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
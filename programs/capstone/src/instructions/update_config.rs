@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use super::error::AuctionError;
+use crate::Config;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ AuctionError::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    /// Replaces the fee settings. Passing a different `new_admin` hands
+    /// control of the config over to that key.
+    pub fn update_config(
+        &mut self,
+        fee_bps: u16,
+        fee_recipient: Pubkey,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        require!(fee_bps <= Config::MAX_FEE_BPS, AuctionError::InvalidFeeBps);
+
+        self.config.fee_bps = fee_bps;
+        self.config.fee_recipient = fee_recipient;
+        self.config.admin = new_admin;
+
+        Ok(())
+    }
+}
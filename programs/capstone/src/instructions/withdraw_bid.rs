@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::error::AuctionError;
+use super::escrow::BidRefund;
+use crate::{Auction, BidWithdrawn, Bids, SettlementMode};
+
+#[derive(Accounts)]
+pub struct WithdrawBid<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    /// CHECK: Only receives the withdrawal penalty on native-SOL auctions
+    #[account(mut, address = auction.maker)]
+    pub maker: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        mut,
+        close = bidder,
+        seeds = [b"bids", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid_record.bump,
+        has_one = bidder,
+    )]
+    pub bid_record: Account<'info, Bids>,
+
+    /// Not needed on native-SOL auctions, where lamports go back to `bidder`
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        associated_token::mint = bid_mint,
+        associated_token::authority = bidder,
+    )]
+    pub bidder_bid_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = auction,
+    )]
+    pub vault_bid: InterfaceAccount<'info, TokenAccount>,
+
+    /// The mint this bidder escrowed in
+    #[account(address = bid_record.mint)]
+    pub bid_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collects the withdrawal penalty. Only required when a penalty applies
+    /// on an SPL auction.
+    #[account(
+        mut,
+        associated_token::mint = bid_mint,
+        associated_token::authority = maker,
+    )]
+    pub maker_bid_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawBid<'info> {
+    /// `hook_accounts` are forwarded to the bid mint's transfer hook, if any.
+    pub fn withdraw_bid(&mut self, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        // Withdrawals are only open while bidding is
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp < self.auction.end_time,
+            AuctionError::AuctionEnded
+        );
+        require!(!self.auction.resolved, AuctionError::AlreadyResolved);
+
+        // A leading bid is a commitment to buy. A second-price auction may
+        // also be pricing its leader off this bid, so nothing leaves there.
+        let leading = if self.auction.winners > 1 {
+            self.auction
+                .leaderboard
+                .iter()
+                .any(|entry| entry.bidder == self.bidder.key())
+        } else {
+            self.auction.highest_bidder == self.bidder.key()
+        };
+        require!(
+            !leading && self.auction.settlement_mode == SettlementMode::FirstPrice,
+            AuctionError::CannotWithdrawBid
+        );
+
+        let amount = self.bid_record.amount;
+        self.auction.escrowed -= amount;
+
+        // Preparing the PDA signatures to authorize the vault transfer
+        let seed_bytes = self.auction.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            self.auction.maker.as_ref(),
+            seed_bytes.as_ref(),
+            &[self.auction.bump],
+        ]];
+
+        let penalty =
+            (amount as u128 * self.auction.bid_rules.withdraw_penalty_bps as u128 / 10_000) as u64;
+        let refund_amount = amount - penalty;
+
+        BidRefund {
+            auction: &self.auction,
+            bidder: &self.bidder.to_account_info(),
+            maker: &self.maker,
+            bidder_bid_ata: self.bidder_bid_ata.as_ref(),
+            maker_bid_ata: self.maker_bid_ata.as_ref(),
+            vault_bid: &self.vault_bid,
+            bid_mint: &self.bid_mint,
+            token_program: &self.token_program,
+            hook_accounts,
+        }
+        .release(refund_amount, penalty, signer_seeds)?;
+
+        emit!(BidWithdrawn {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            amount: refund_amount,
+            penalty,
+        });
+
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
+pub mod events;
 pub mod instructions;
 pub mod state;
 
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -11,29 +13,256 @@ declare_id!("GPfsmgJRLLxaWScL2PPEt5TAgAjzNYTaMuzmsPipnfSv");
 pub mod capstone {
     use super::*;
 
-    pub fn make_auction(
-        ctx: Context<MakeAuction>,
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_bps: u16,
+        fee_recipient: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts
+            .initialize_config(fee_bps, fee_recipient, &ctx.bumps)
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_bps: u16,
+        fee_recipient: Pubkey,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts
+            .update_config(fee_bps, fee_recipient, new_admin)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, MakeAuction<'info>>,
         seed: u64,
         end_time: i64,
         deposit_amount: u64,
+        reserve_price: u64,
+        bid_rules: BidRules,
+        soft_close: SoftClose,
+        buy_now_price: u64,
+        kind: AuctionKind,
+        settlement_mode: SettlementMode,
+        start_time: i64,
+        winners: u8,
+        native_sol: bool,
+        payment_mints: Vec<PaymentMint>,
+        bundle_size: u8,
+        required_collection: Option<Pubkey>,
+        allowlist_root: Option<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.init_auction(
+            seed,
+            end_time,
+            reserve_price,
+            bid_rules,
+            soft_close,
+            buy_now_price,
+            kind,
+            settlement_mode,
+            start_time,
+            winners,
+            native_sol,
+            payment_mints,
+            required_collection,
+            allowlist_root,
+            &ctx.bumps,
+        )?;
+        ctx.accounts
+            .deposit_prize(deposit_amount, ctx.remaining_accounts)?;
+        ctx.accounts
+            .deposit_bundle(bundle_size, ctx.remaining_accounts)?;
+        ctx.accounts.emit_created();
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_compressed_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, MakeCompressedAuction<'info>>,
+        seed: u64,
+        end_time: i64,
+        reserve_price: u64,
+        bid_rules: BidRules,
+        soft_close: SoftClose,
+        kind: AuctionKind,
+        settlement_mode: SettlementMode,
+        start_time: i64,
+        leaf: CompressedNft,
+        root: [u8; 32],
+        required_collection: Option<Pubkey>,
+        allowlist_root: Option<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.make_compressed_auction(
+            seed,
+            end_time,
+            reserve_price,
+            bid_rules,
+            soft_close,
+            kind,
+            settlement_mode,
+            start_time,
+            leaf,
+            root,
+            required_collection,
+            allowlist_root,
+            ctx.remaining_accounts,
+            &ctx.bumps,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_core_auction(
+        ctx: Context<MakeCoreAuction>,
+        seed: u64,
+        end_time: i64,
+        reserve_price: u64,
+        bid_rules: BidRules,
+        soft_close: SoftClose,
+        kind: AuctionKind,
+        settlement_mode: SettlementMode,
+        start_time: i64,
+        required_collection: Option<Pubkey>,
+        allowlist_root: Option<[u8; 32]>,
     ) -> Result<()> {
-        ctx.accounts.init_auction(seed, end_time, &ctx.bumps)?;
-        ctx.accounts.deposit_prize(deposit_amount)
+        ctx.accounts.make_core_auction(
+            seed,
+            end_time,
+            reserve_price,
+            bid_rules,
+            soft_close,
+            kind,
+            settlement_mode,
+            start_time,
+            required_collection,
+            allowlist_root,
+            &ctx.bumps,
+        )
     }
 
-    pub fn bid(ctx: Context<Bid>, additional_amount: u64) -> Result<()> {
-        ctx.accounts.bid(additional_amount, &ctx.bumps)
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_programmable_auction(
+        ctx: Context<MakeProgrammableAuction>,
+        seed: u64,
+        end_time: i64,
+        reserve_price: u64,
+        bid_rules: BidRules,
+        soft_close: SoftClose,
+        kind: AuctionKind,
+        settlement_mode: SettlementMode,
+        start_time: i64,
+        required_collection: Option<Pubkey>,
+        allowlist_root: Option<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.make_programmable_auction(
+            seed,
+            end_time,
+            reserve_price,
+            bid_rules,
+            soft_close,
+            kind,
+            settlement_mode,
+            start_time,
+            required_collection,
+            allowlist_root,
+            &ctx.bumps,
+        )
     }
 
-    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
-        ctx.accounts.refund_loser()
+    pub fn bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, Bid<'info>>,
+        additional_amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.bid(
+            additional_amount,
+            &proof,
+            ctx.remaining_accounts,
+            &ctx.bumps,
+        )
+    }
+
+    pub fn commit_bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, CommitBid<'info>>,
+        commitment: [u8; 32],
+        deposit: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.commit_bid(
+            commitment,
+            deposit,
+            &proof,
+            ctx.remaining_accounts,
+            &ctx.bumps,
+        )
+    }
+
+    pub fn reveal_bid(ctx: Context<RevealBid>, amount: u64, salt: [u8; 32]) -> Result<()> {
+        ctx.accounts.reveal_bid(amount, salt)
+    }
+
+    pub fn buy_now<'info>(ctx: Context<'_, '_, '_, 'info, BuyNow<'info>>) -> Result<()> {
+        ctx.accounts.buy_now(ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn withdraw_bid<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawBid<'info>>) -> Result<()> {
+        ctx.accounts.withdraw_bid(ctx.remaining_accounts)
+    }
+
+    pub fn claim_refund<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRefund<'info>>) -> Result<()> {
+        ctx.accounts.refund_loser(ctx.remaining_accounts)
+    }
+
+    pub fn resolve_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveAuction<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.resolve(ctx.remaining_accounts)
+    }
+
+    pub fn resolve_compressed_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveCompressedAuction<'info>>,
+        root: [u8; 32],
+        proof_len: u8,
+        royalties: LeafRoyalties,
+    ) -> Result<()> {
+        ctx.accounts
+            .resolve_compressed(root, proof_len, royalties, ctx.remaining_accounts)
+    }
+
+    pub fn resolve_core_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveCoreAuction<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.resolve_core(ctx.remaining_accounts)
+    }
+
+    pub fn resolve_programmable_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveProgrammableAuction<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.resolve_programmable(ctx.remaining_accounts)
+    }
+
+    pub fn claim_prize<'info>(ctx: Context<'_, '_, '_, 'info, ClaimPrize<'info>>) -> Result<()> {
+        ctx.accounts.claim_prize(ctx.remaining_accounts)
+    }
+
+    pub fn cancel_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelAuction<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.cancel(ctx.remaining_accounts)
+    }
+
+    pub fn cancel_compressed_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelCompressedAuction<'info>>,
+        root: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.cancel_compressed(root, ctx.remaining_accounts)
     }
 
-    pub fn resolve_auction(ctx: Context<ResolveAuction>) -> Result<()> {
-        ctx.accounts.resolve()
+    pub fn cancel_core_auction(ctx: Context<CancelCoreAuction>) -> Result<()> {
+        ctx.accounts.cancel_core()
     }
 
-    pub fn cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
-        ctx.accounts.cancel()
+    pub fn cancel_programmable_auction(ctx: Context<CancelProgrammableAuction>) -> Result<()> {
+        ctx.accounts.cancel_programmable()
     }
 }
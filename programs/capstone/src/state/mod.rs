@@ -1,4 +1,13 @@
 use anchor_lang::prelude::*;
+use solana_keccak_hasher as keccak;
+use solana_sha256_hasher::hashv;
+
+/// Upper bound on `Auction::winners`, which sizes the leaderboard
+pub const MAX_WINNERS: usize = 10;
+/// Upper bound on the extra NFTs a bundle auction holds beside `nft_mint`
+pub const MAX_BUNDLE_SIZE: usize = 5;
+/// Upper bound on the payment mints accepted beside `bid_mint`
+pub const MAX_PAYMENT_MINTS: usize = 4;
 
 #[account]
 #[derive(InitSpace)]
@@ -12,13 +21,643 @@ pub struct Auction {
     pub resolved: bool,
     pub highest_bidder: Pubkey,
     pub highest_bid_amount: u64,
+    pub reserve_price: u64,
+    pub bid_rules: BidRules,
+    pub soft_close: SoftClose,
+    /// Seconds `end_time` has been pushed back by soft-close extensions
+    pub extended_by: i64,
+    /// Price at which a bidder can end the auction immediately. Zero disables it.
+    pub buy_now_price: u64,
+    pub kind: AuctionKind,
+    /// Best bid below the leader's, tracked for second-price settlement
+    pub second_highest_bid_amount: u64,
+    pub settlement_mode: SettlementMode,
+    /// Bids (and Dutch price decay) only begin at this timestamp
+    pub start_time: i64,
+    /// Number of prize tokens held in `vault_nft`; above one for SFT lots
+    pub prize_amount: u64,
+    /// Units awarded, one to each of the top bidders. One is a classic
+    /// single-winner auction.
+    pub winners: u8,
+    /// Multi-winner auctions only: the best `winners` bids, highest first
+    #[max_len(MAX_WINNERS)]
+    pub leaderboard: Vec<LeaderboardEntry>,
+    /// Bundle auctions only: NFTs sold together with `nft_mint`, one vault each
+    #[max_len(MAX_BUNDLE_SIZE)]
+    pub bundle_mints: Vec<Pubkey>,
+    /// Bids are escrowed as lamports on this PDA instead of in `vault_bid`.
+    /// `bid_mint` is then the wrapped SOL mint.
+    pub native_sol: bool,
+    /// Total of every bid still held in escrow. On native-SOL auctions the
+    /// last refund or claim tears the auction down once this reaches zero.
+    pub escrowed: u64,
+    /// Extra mints bids may be placed in, beside `bid_mint`. Amounts on the
+    /// leaderboard, the reserve and the bid rules are all in `bid_mint` units.
+    #[max_len(MAX_PAYMENT_MINTS)]
+    pub payment_mints: Vec<PaymentMint>,
+    /// Set when the prize is a compressed NFT held in a Bubblegum tree
+    /// instead of `vault_nft`. `nft_mint` then holds the asset id.
+    pub compressed: Option<CompressedNft>,
+    /// Set when the prize is a Metaplex Core asset owned by the Auction PDA
+    /// instead of `vault_nft`. `nft_mint` then holds the asset address.
+    pub core: Option<CoreAsset>,
+    /// Set when the prize is a programmable NFT, which only moves through
+    /// Token Metadata's transfer instruction.
+    pub programmable: bool,
+    /// Holders-only auctions: bidders must hold an NFT that is a verified
+    /// member of this Metaplex collection.
+    pub required_collection: Option<Pubkey>,
+    /// Private sales: root of a Merkle tree of allowed bidders, built with
+    /// `Auction::allowlist_leaf` and `Auction::allowlist_node`.
+    pub allowlist_root: Option<[u8; 32]>,
+    /// Protocol cut of the sale in basis points, copied from `Config` when the
+    /// auction is made
+    pub fee_bps: u16,
+}
+
+/// A whitelisted payment mint and its fixed rate against `bid_mint`: one
+/// base unit of `mint` counts as `numerator / denominator` base units of
+/// `bid_mint`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct PaymentMint {
+    pub mint: Pubkey,
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+/// Where a compressed NFT prize sits in its Bubblegum tree. The data and
+/// creator hashes don't change when the leaf changes hands, so with the
+/// tree's current root and a proof they are all a transfer needs.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct CompressedNft {
+    pub merkle_tree: Pubkey,
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+}
+
+impl CompressedNft {
+    /// Whether `royalties` are the terms this leaf was minted with: they must
+    /// hash to its data and creator hashes the way Bubblegum derives them.
+    pub fn matches(&self, royalties: &LeafRoyalties) -> bool {
+        let data_hash = keccak::hashv(&[
+            &royalties.metadata_hash,
+            &royalties.seller_fee_basis_points.to_le_bytes(),
+        ]);
+        let creators: Vec<Vec<u8>> = royalties
+            .creators
+            .iter()
+            .map(|creator| {
+                [
+                    creator.address.as_ref(),
+                    &[creator.verified as u8],
+                    &[creator.share],
+                ]
+                .concat()
+            })
+            .collect();
+        let creator_hash = keccak::hashv(&creators.iter().map(Vec::as_slice).collect::<Vec<_>>());
+
+        data_hash.to_bytes() == self.data_hash && creator_hash.to_bytes() == self.creator_hash
+    }
+}
+
+/// The royalty terms of a compressed NFT. The leaf only keeps their hashes,
+/// so settlement takes them as an argument and checks them against it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LeafRoyalties {
+    /// keccak256 of the leaf's Borsh-serialized `MetadataArgs`
+    pub metadata_hash: [u8; 32],
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<LeafCreator>,
+}
+
+/// A creator listed in a compressed NFT's metadata.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct LeafCreator {
+    pub address: Pubkey,
+    pub verified: bool,
+    /// Percentage of the seller fee
+    pub share: u8,
+}
+
+/// A Metaplex Core prize. Core asks for the asset's collection, if it has
+/// one, on every transfer.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct CoreAsset {
+    pub collection: Option<Pubkey>,
+}
+
+/// A bidder's total on a multi-winner auction's leaderboard.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct LeaderboardEntry {
+    pub bidder: Pubkey,
+    pub amount: u64,
+}
+
+/// What the winner of an English or sealed-bid auction pays.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum SettlementMode {
+    /// The winner pays their own bid.
+    FirstPrice,
+    /// Vickrey: the winner pays the runner-up's bid (never less than the
+    /// reserve or starting price) and gets the difference back.
+    SecondPrice,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum AuctionKind {
+    /// Ascending open bids through `bid`, settled by `resolve_auction`.
+    English,
+    /// Descending price from `start_price` at the auction's `start_time`
+    /// down to `floor_price` at `end_time`. The first buyer through `buy_now` wins.
+    Dutch {
+        start_price: u64,
+        floor_price: u64,
+        /// Drop the price in steps of this many seconds. Zero decays linearly.
+        step_interval: i64,
+    },
+    /// Bidders commit a hash plus an escrowed deposit until `end_time`, then
+    /// reveal amount and salt until `reveal_end`. The highest valid reveal wins.
+    Sealed {
+        reveal_end: i64,
+        /// Cut of an unrevealed deposit kept by the maker on refund, in basis points
+        unrevealed_penalty_bps: u16,
+    },
+}
+
+/// Maker-configured constraints on how bids may take the lead.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct BidRules {
+    /// Smallest total any bidder may have on the book.
+    pub start_price: u64,
+    /// Absolute amount a new leader must beat the current top bid by.
+    pub min_increment: u64,
+    /// Relative increment over the current top bid, in basis points. When
+    /// both increments are set, the larger one applies.
+    pub min_increment_bps: u16,
+    /// Share of a bid kept by the maker when it is withdrawn before the
+    /// deadline, in basis points.
+    pub withdraw_penalty_bps: u16,
+}
+
+/// Anti-sniping settings. A lead change within `window` seconds of the
+/// deadline pushes `end_time` back by `extension` seconds.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct SoftClose {
+    pub window: i64,
+    pub extension: i64,
+    /// Cap on the total extension in seconds. Zero means uncapped.
+    pub max_extension: i64,
+}
+
+impl Auction {
+    /// Longest an auction may run, counted from creation
+    pub const MAX_DURATION: i64 = 90 * 24 * 60 * 60;
+
+    /// The protocol's share of a sale of `amount`, rounded down.
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / 10_000) as u64
+    }
+
+    /// Whether the current top bid clears the maker's floor. A reserve of
+    /// zero is always met.
+    pub fn reserve_met(&self) -> bool {
+        self.highest_bid_amount >= self.reserve_price
+    }
+
+    /// Marks a single-winner auction resolved and returns whether the
+    /// reserve was met. If it was, the winner's escrowed `winner_bid` stops
+    /// counting toward `escrowed`, since it is about to be paid out.
+    pub fn mark_resolved(&mut self, winner_bid: u64) -> bool {
+        self.resolved = true;
+        let reserve_met = self.reserve_met();
+        if reserve_met {
+            self.escrowed -= winner_bid;
+        }
+        reserve_met
+    }
+
+    /// The smallest total that can take the lead from the current top bid.
+    pub fn min_leading_bid(&self) -> u64 {
+        if self.highest_bid_amount == 0 {
+            return self.bid_rules.start_price.max(1);
+        }
+        self.outbid(self.highest_bid_amount)
+    }
+
+    /// The smallest total that earns a place on a multi-winner leaderboard:
+    /// the opening price while a slot is free, otherwise the lowest entry
+    /// plus the minimum increment.
+    pub fn min_leaderboard_bid(&self) -> u64 {
+        match self.leaderboard.get(self.winners as usize - 1) {
+            Some(lowest) => self.outbid(lowest.amount),
+            None => self.bid_rules.start_price.max(1),
+        }
+    }
+
+    /// `amount` raised by the larger of the absolute and relative increments.
+    fn outbid(&self, amount: u64) -> u64 {
+        let bps_increment =
+            (amount as u128).saturating_mul(self.bid_rules.min_increment_bps as u128) / 10_000;
+        let increment = self
+            .bid_rules
+            .min_increment
+            .max(u64::try_from(bps_increment).unwrap_or(u64::MAX))
+            .max(1);
+
+        amount.saturating_add(increment)
+    }
+
+    /// Places or moves `bidder` on the multi-winner leaderboard, bumping the
+    /// lowest entry off once it is full. Ties keep the earlier bid ahead.
+    pub fn rank(&mut self, bidder: Pubkey, amount: u64) {
+        self.leaderboard.retain(|entry| entry.bidder != bidder);
+        let position = self
+            .leaderboard
+            .iter()
+            .position(|entry| entry.amount < amount)
+            .unwrap_or(self.leaderboard.len());
+        self.leaderboard
+            .insert(position, LeaderboardEntry { bidder, amount });
+        self.leaderboard.truncate(self.winners as usize);
+
+        self.highest_bidder = self.leaderboard[0].bidder;
+        self.highest_bid_amount = self.leaderboard[0].amount;
+    }
+
+    /// Whether bids may be placed in `mint`.
+    pub fn accepts(&self, mint: &Pubkey) -> bool {
+        *mint == self.bid_mint || self.payment_mints.iter().any(|p| p.mint == *mint)
+    }
+
+    /// Converts `amount` of an accepted mint into `bid_mint` units, rounding down.
+    pub fn normalize(&self, mint: &Pubkey, amount: u64) -> u64 {
+        match self.payment_mints.iter().find(|p| p.mint == *mint) {
+            Some(p) => {
+                let value = amount as u128 * p.numerator as u128 / p.denominator as u128;
+                u64::try_from(value).unwrap_or(u64::MAX)
+            }
+            None => amount,
+        }
+    }
+
+    /// What a winner who escrowed `escrowed` of `mint` pays at settlement,
+    /// in that mint. A second-price clearing price is converted back from
+    /// `bid_mint` units, rounding up so the maker is never short.
+    pub fn price_in(&self, mint: &Pubkey, escrowed: u64) -> u64 {
+        let price = self.clearing_price();
+        let Some(p) = self.payment_mints.iter().find(|p| p.mint == *mint) else {
+            return price;
+        };
+        if price >= self.highest_bid_amount {
+            return escrowed;
+        }
+        let value = (price as u128 * p.denominator as u128).div_ceil(p.numerator as u128);
+        u64::try_from(value).unwrap_or(u64::MAX).min(escrowed)
+    }
+
+    /// Whether every bid has left escrow, given `vault_bid`'s balance. When
+    /// bids sit in several vaults, or as lamports on this PDA next to its
+    /// rent, the running total is what counts.
+    pub fn escrow_empty(&self, vault_bid_amount: u64) -> bool {
+        if self.native_sol || !self.payment_mints.is_empty() {
+            self.escrowed == 0
+        } else {
+            vault_bid_amount == 0
+        }
+    }
+
+    /// Whether `bidder` may bid: always, unless the auction has an allowlist,
+    /// in which case `proof` must lead from the bidder's leaf to its root.
+    pub fn allows(&self, bidder: &Pubkey, proof: &[[u8; 32]]) -> bool {
+        let Some(root) = self.allowlist_root else {
+            return true;
+        };
+        let computed = proof
+            .iter()
+            .fold(Self::allowlist_leaf(bidder), |node, sibling| {
+                Self::allowlist_node(&node, sibling)
+            });
+        computed == root
+    }
+
+    /// `sha256(0x00 || bidder)`. The prefixes keep a leaf from ever being
+    /// passed off as an inner node.
+    pub fn allowlist_leaf(bidder: &Pubkey) -> [u8; 32] {
+        hashv(&[&[0], bidder.as_ref()]).to_bytes()
+    }
+
+    /// `sha256(0x01 || min(a, b) || max(a, b))`. Sorting the pair means a
+    /// proof is just the siblings, without left/right flags.
+    pub fn allowlist_node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let (left, right) = if a <= b { (a, b) } else { (b, a) };
+        hashv(&[&[1], left, right]).to_bytes()
+    }
+
+    /// Whether `bidder` won a unit of a multi-winner auction.
+    pub fn is_winner(&self, bidder: &Pubkey) -> bool {
+        self.leaderboard
+            .iter()
+            .any(|entry| entry.bidder == *bidder && entry.amount >= self.reserve_price)
+    }
+
+    /// Number of leaderboard entries that clear the reserve.
+    pub fn winning_entries(&self) -> u64 {
+        self.leaderboard
+            .iter()
+            .filter(|entry| entry.amount >= self.reserve_price)
+            .count() as u64
+    }
+
+    /// Records a new best bid of `amount`, demoting the old leader's amount
+    /// to the runner-up slot.
+    pub fn take_lead(&mut self, bidder: Pubkey, amount: u64) {
+        if self.highest_bidder != bidder {
+            self.second_highest_bid_amount = self.highest_bid_amount;
+        }
+        self.highest_bidder = bidder;
+        self.highest_bid_amount = amount;
+    }
+
+    /// What the winner actually pays at settlement.
+    pub fn clearing_price(&self) -> u64 {
+        match self.settlement_mode {
+            SettlementMode::FirstPrice => self.highest_bid_amount,
+            SettlementMode::SecondPrice => self
+                .second_highest_bid_amount
+                .max(self.reserve_price)
+                .max(self.bid_rules.start_price)
+                .min(self.highest_bid_amount),
+        }
+    }
+
+    /// When the auction can be settled and losers refunded. For sealed-bid
+    /// auctions that is after the reveal window, not at `end_time`.
+    pub fn settle_time(&self) -> i64 {
+        match self.kind {
+            AuctionKind::Sealed { reveal_end, .. } => reveal_end,
+            _ => self.end_time,
+        }
+    }
+
+    /// The price `buy_now` settles at right now, if instant purchase is
+    /// available: the current Dutch price, or the English buy-now price.
+    pub fn instant_price(&self, now: i64) -> Option<u64> {
+        match self.kind {
+            AuctionKind::English => (self.buy_now_price > 0).then_some(self.buy_now_price),
+            AuctionKind::Dutch {
+                start_price,
+                floor_price,
+                step_interval,
+            } => {
+                let duration = self.end_time - self.start_time;
+                let mut elapsed = now.clamp(self.start_time, self.end_time) - self.start_time;
+                if step_interval > 0 {
+                    elapsed -= elapsed % step_interval;
+                }
+
+                let decay =
+                    (start_price - floor_price) as u128 * elapsed as u128 / duration as u128;
+                Some(start_price - decay as u64)
+            }
+            AuctionKind::Sealed { .. } => None,
+        }
+    }
 }
 
 #[account]
 #[derive(InitSpace)]
 pub struct Bids {
     pub bidder: Pubkey,
+    /// Tokens this bidder has escrowed in `vault_bid`. For sealed bids this
+    /// is the deposit, which may exceed the revealed bid.
     pub amount: u64,
     pub bump: u8,
     pub refunded: bool,
+    /// Sealed bids only: `sha256(bidder || amount_le || salt)`
+    pub commitment: [u8; 32],
+    pub revealed: bool,
+    /// The mint this bidder escrowed in, `bid_mint` or one of the auction's
+    /// payment mints
+    pub mint: Pubkey,
+}
+
+impl Bids {
+    pub fn commitment_for(bidder: &Pubkey, amount: u64, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[bidder.as_ref(), &amount.to_le_bytes(), salt]).to_bytes()
+    }
+}
+
+/// Global marketplace settings, a singleton PDA at `[b"config"]`.
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    /// Protocol cut of every sale, in basis points
+    pub fee_bps: u16,
+    pub fee_recipient: Pubkey,
+    pub bump: u8,
+}
+
+impl Config {
+    pub const MAX_FEE_BPS: u16 = 1_000;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auction_with_allowlist(allowlist_root: Option<[u8; 32]>) -> Auction {
+        Auction {
+            seed: 0,
+            maker: Pubkey::new_unique(),
+            nft_mint: Pubkey::new_unique(),
+            bid_mint: Pubkey::new_unique(),
+            end_time: 0,
+            bump: 0,
+            resolved: false,
+            highest_bidder: Pubkey::default(),
+            highest_bid_amount: 0,
+            reserve_price: 0,
+            bid_rules: BidRules::default(),
+            soft_close: SoftClose::default(),
+            extended_by: 0,
+            buy_now_price: 0,
+            kind: AuctionKind::English,
+            second_highest_bid_amount: 0,
+            settlement_mode: SettlementMode::FirstPrice,
+            start_time: 0,
+            prize_amount: 1,
+            winners: 1,
+            leaderboard: Vec::new(),
+            bundle_mints: Vec::new(),
+            native_sol: false,
+            escrowed: 0,
+            payment_mints: Vec::new(),
+            compressed: None,
+            core: None,
+            programmable: false,
+            required_collection: None,
+            allowlist_root,
+            fee_bps: 0,
+        }
+    }
+
+    /// Builds the allowlist tree bottom-up, carrying an unpaired node up a
+    /// level as is. Returns the root and each bidder's proof.
+    fn build_allowlist(bidders: &[Pubkey]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+        let mut level: Vec<[u8; 32]> = bidders.iter().map(Auction::allowlist_leaf).collect();
+        // Position of each bidder's ancestor in the current level
+        let mut positions: Vec<usize> = (0..bidders.len()).collect();
+        let mut proofs = vec![Vec::new(); bidders.len()];
+
+        while level.len() > 1 {
+            for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+                if let Some(sibling) = level.get(*position ^ 1) {
+                    proof.push(*sibling);
+                }
+                *position /= 2;
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => Auction::allowlist_node(a, b),
+                    [lone] => *lone,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+
+        (level[0], proofs)
+    }
+
+    fn leaf_for(royalties: &LeafRoyalties) -> CompressedNft {
+        let mut leaf = CompressedNft {
+            merkle_tree: Pubkey::new_unique(),
+            data_hash: [0; 32],
+            creator_hash: [0; 32],
+            nonce: 0,
+            index: 0,
+        };
+        leaf.data_hash = keccak::hashv(&[
+            &royalties.metadata_hash,
+            &royalties.seller_fee_basis_points.to_le_bytes(),
+        ])
+        .to_bytes();
+        let creator_data: Vec<u8> = royalties
+            .creators
+            .iter()
+            .flat_map(|c| [c.address.as_ref(), &[c.verified as u8, c.share]].concat())
+            .collect();
+        leaf.creator_hash = keccak::hash(&creator_data).to_bytes();
+        leaf
+    }
+
+    #[test]
+    fn leaf_matches_its_minted_royalties() {
+        let royalties = LeafRoyalties {
+            metadata_hash: [7; 32],
+            seller_fee_basis_points: 500,
+            creators: vec![
+                LeafCreator {
+                    address: Pubkey::new_unique(),
+                    verified: true,
+                    share: 60,
+                },
+                LeafCreator {
+                    address: Pubkey::new_unique(),
+                    verified: false,
+                    share: 40,
+                },
+            ],
+        };
+        let leaf = leaf_for(&royalties);
+        assert!(leaf.matches(&royalties));
+
+        // Every term is bound by the leaf's hashes
+        let mut lower_fee = royalties.clone();
+        lower_fee.seller_fee_basis_points = 0;
+        assert!(!leaf.matches(&lower_fee));
+
+        let mut reshared = royalties.clone();
+        reshared.creators[0].share = 40;
+        reshared.creators[1].share = 60;
+        assert!(!leaf.matches(&reshared));
+
+        let mut dropped = royalties.clone();
+        dropped.creators.pop();
+        assert!(!leaf.matches(&dropped));
+    }
+
+    #[test]
+    fn leaf_without_creators_hashes_an_empty_list() {
+        let royalties = LeafRoyalties {
+            metadata_hash: [0; 32],
+            seller_fee_basis_points: 0,
+            creators: Vec::new(),
+        };
+        // keccak256 of no input, which Bubblegum stores for creatorless leaves
+        let leaf = leaf_for(&royalties);
+        assert_eq!(
+            leaf.creator_hash,
+            [
+                0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7,
+                0x03, 0xc0, 0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04,
+                0x5d, 0x85, 0xa4, 0x70,
+            ]
+        );
+        assert!(leaf.matches(&royalties));
+    }
+
+    #[test]
+    fn open_auction_allows_anyone() {
+        let auction = auction_with_allowlist(None);
+        assert!(auction.allows(&Pubkey::new_unique(), &[]));
+    }
+
+    #[test]
+    fn allowlist_accepts_every_listed_bidder() {
+        for size in 1..=9 {
+            let bidders: Vec<Pubkey> = (0..size).map(|_| Pubkey::new_unique()).collect();
+            let (root, proofs) = build_allowlist(&bidders);
+            let auction = auction_with_allowlist(Some(root));
+
+            for (bidder, proof) in bidders.iter().zip(&proofs) {
+                assert!(auction.allows(bidder, proof), "tree of {size}");
+            }
+        }
+    }
+
+    #[test]
+    fn allowlist_rejects_outsiders_and_borrowed_proofs() {
+        let bidders: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let (root, proofs) = build_allowlist(&bidders);
+        let auction = auction_with_allowlist(Some(root));
+
+        let outsider = Pubkey::new_unique();
+        assert!(!auction.allows(&outsider, &[]));
+        for proof in &proofs {
+            assert!(!auction.allows(&outsider, proof));
+        }
+        // A listed bidder's proof only works for that bidder
+        assert!(!auction.allows(&bidders[0], &proofs[1]));
+        assert!(!auction.allows(&bidders[0], &[]));
+    }
+
+    #[test]
+    fn allowlist_rejects_inner_nodes_as_leaves() {
+        let bidders: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let (root, proofs) = build_allowlist(&bidders);
+        let auction = auction_with_allowlist(Some(root));
+
+        // The parent of the first two leaves, posed as a bidder key, with
+        // the rest of the first bidder's proof
+        let parent = Auction::allowlist_node(
+            &Auction::allowlist_leaf(&bidders[0]),
+            &Auction::allowlist_leaf(&bidders[1]),
+        );
+        assert!(!auction.allows(&Pubkey::new_from_array(parent), &proofs[0][1..]));
+    }
 }
@@ -1,13 +1,24 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Capstone } from "../target/types/capstone";
+import { TransferHook } from "../target/types/transfer_hook";
 import { expect } from "chai";
+import { createHash, randomBytes } from "crypto";
+import { keccak_256 } from "@noble/hashes/sha3";
 import {
   getAssociatedTokenAddressSync,
   createAssociatedTokenAccountInstruction,
   createMint,
   mintTo,
+  getAccount,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeTransferHookInstruction,
+  ExtensionType,
+  getMintLen,
+  NATIVE_MINT,
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID
 } from "@solana/spl-token";
 
@@ -21,6 +32,8 @@ describe("capstone-auction", () => {
   const bidder1 = anchor.web3.Keypair.generate();
   const bidder2 = anchor.web3.Keypair.generate();
   const crank = anchor.web3.Keypair.generate();
+  const feeRecipient = anchor.web3.Keypair.generate();
+  const FEE_BPS = 500; // 5% protocol cut
 
   let nftMint: anchor.web3.PublicKey;
   let bidMint: anchor.web3.PublicKey;
@@ -29,9 +42,50 @@ describe("capstone-auction", () => {
   let bidder1BidAta: anchor.web3.PublicKey;
   let bidder2BidAta: anchor.web3.PublicKey;
   let winnerNftAta: anchor.web3.PublicKey;
+  let feeRecipientBidAta: anchor.web3.PublicKey;
+
+  // Loaded from tests/fixtures, see the `fixtures` script in package.json
+  const TOKEN_METADATA_PROGRAM_ID = new anchor.web3.PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+  const [configPda] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
 
   const seed1 = new anchor.BN(Math.floor(Math.random() * 1001));
   const seed2 = new anchor.BN(Math.floor(Math.random() * 1002));
+  const seed3 = new anchor.BN(Math.floor(Math.random() * 1003) + 2000);
+  const seed4 = new anchor.BN(Math.floor(Math.random() * 1004) + 4000);
+  const seed5 = new anchor.BN(Math.floor(Math.random() * 1005) + 6000);
+  const seed6 = new anchor.BN(Math.floor(Math.random() * 1006) + 8000);
+  const seed7 = new anchor.BN(Math.floor(Math.random() * 1007) + 10000);
+  const seed8 = new anchor.BN(Math.floor(Math.random() * 1008) + 12000);
+  const seed9 = new anchor.BN(Math.floor(Math.random() * 1009) + 14000);
+  const seed10 = new anchor.BN(Math.floor(Math.random() * 1010) + 16000);
+  const seed11 = new anchor.BN(Math.floor(Math.random() * 1011) + 18000);
+  const seed12 = new anchor.BN(Math.floor(Math.random() * 1012) + 20000);
+  const seed13 = new anchor.BN(Math.floor(Math.random() * 1013) + 22000);
+  const seed14 = new anchor.BN(Math.floor(Math.random() * 1014) + 24000);
+  const seed15 = new anchor.BN(Math.floor(Math.random() * 1015) + 26000);
+  const seed16 = new anchor.BN(Math.floor(Math.random() * 1016) + 28000);
+  const seed17 = new anchor.BN(Math.floor(Math.random() * 1017) + 30000);
+  const seed18 = new anchor.BN(Math.floor(Math.random() * 1018) + 32000);
+  const seed19 = new anchor.BN(Math.floor(Math.random() * 1019) + 34000);
+  const seed20 = new anchor.BN(Math.floor(Math.random() * 1020) + 36000);
+  const seed21 = new anchor.BN(Math.floor(Math.random() * 1021) + 38000);
+  const seed22 = new anchor.BN(Math.floor(Math.random() * 1022) + 40000);
+  const seed23 = new anchor.BN(Math.floor(Math.random() * 1023) + 42000);
+  const seed24 = new anchor.BN(Math.floor(Math.random() * 1024) + 44000);
+  const seed25 = new anchor.BN(Math.floor(Math.random() * 1025) + 46000);
+  const seed26 = new anchor.BN(Math.floor(Math.random() * 1026) + 48000);
+  const seed27 = new anchor.BN(Math.floor(Math.random() * 1027) + 50000);
+  const seed28 = new anchor.BN(Math.floor(Math.random() * 1028) + 52000);
+  const seed29 = new anchor.BN(Math.floor(Math.random() * 1029) + 54000);
+  const seed30 = new anchor.BN(Math.floor(Math.random() * 1030) + 56000);
+  const seed31 = new anchor.BN(Math.floor(Math.random() * 1031) + 58000);
+  const seed32 = new anchor.BN(Math.floor(Math.random() * 1032) + 60000);
+  const seed33 = new anchor.BN(Math.floor(Math.random() * 1033) + 62000);
+  const seed34 = new anchor.BN(Math.floor(Math.random() * 1034) + 64000);
+  const seed35 = new anchor.BN(Math.floor(Math.random() * 1035) + 66000);
+  const seed36 = new anchor.BN(Math.floor(Math.random() * 1036) + 68000);
+  const seed37 = new anchor.BN(Math.floor(Math.random() * 1037) + 70000);
   let auctionPda: anchor.web3.PublicKey;
   let vaultNft: anchor.web3.PublicKey;
   let vaultBid: anchor.web3.PublicKey;
@@ -40,6 +94,81 @@ describe("capstone-auction", () => {
 
   let endTime: number;
 
+  // No opening price or increment — anything above the current top bid leads
+  const openRules = { startPrice: new anchor.BN(0), minIncrement: new anchor.BN(0), minIncrementBps: 0, withdrawPenaltyBps: 0 };
+  const english = { english: {} };
+  const firstPrice = { firstPrice: {} };
+  const noSoftClose = { window: new anchor.BN(0), extension: new anchor.BN(0), maxExtension: new anchor.BN(0) };
+
+  type AuctionOptions = {
+    reservePrice?: anchor.BN;
+    bidRules?: typeof openRules;
+    softClose?: typeof noSoftClose;
+    buyNowPrice?: anchor.BN;
+    kind?: object;
+    settlementMode?: object;
+    startTime?: number;
+    prizeAmount?: number;
+    winners?: number;
+    bundleSize?: number;
+    requiredCollection?: anchor.web3.PublicKey;
+    allowlistRoot?: number[];
+    nativeSol?: boolean;
+    paymentMints?: { mint: anchor.web3.PublicKey; numerator: anchor.BN; denominator: anchor.BN }[];
+    royalties?: { sellerFeeBasisPoints: number; creators: { address: anchor.web3.PublicKey; share: number }[] };
+  };
+
+  const metadataPdaFor = (mint: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+
+  // Hand-encoded CreateMetadataAccountV3 so the tests don't need the Metaplex JS SDK
+  const createMetadataIx = (
+    mint: anchor.web3.PublicKey,
+    royalties: NonNullable<AuctionOptions["royalties"]>,
+    collection?: anchor.web3.PublicKey
+  ) => {
+    const borshString = (value: string) => {
+      const len = Buffer.alloc(4);
+      len.writeUInt32LE(Buffer.byteLength(value));
+      return Buffer.concat([len, Buffer.from(value)]);
+    };
+    const sellerFee = Buffer.alloc(2);
+    sellerFee.writeUInt16LE(royalties.sellerFeeBasisPoints);
+    const creatorCount = Buffer.alloc(4);
+    creatorCount.writeUInt32LE(royalties.creators.length);
+
+    const data = Buffer.concat([
+      Buffer.from([33]), // CreateMetadataAccountV3
+      borshString("Capstone Test"),
+      borshString("CAP"),
+      borshString(""),
+      sellerFee,
+      Buffer.from([1]),
+      creatorCount,
+      ...royalties.creators.map((c) => Buffer.concat([c.address.toBuffer(), Buffer.from([0, c.share])])),
+      collection ? Buffer.concat([Buffer.from([1, 0]), collection.toBuffer()]) : Buffer.from([0]), // unverified collection
+      Buffer.from([0]), // no uses
+      Buffer.from([1]), // is_mutable
+      Buffer.from([0]), // no collection details
+    ]);
+
+    return new anchor.web3.TransactionInstruction({
+      programId: TOKEN_METADATA_PROGRAM_ID,
+      keys: [
+        { pubkey: metadataPdaFor(mint), isSigner: false, isWritable: true },
+        { pubkey: mint, isSigner: false, isWritable: false },
+        { pubkey: maker.publicKey, isSigner: true, isWritable: false },
+        { pubkey: maker.publicKey, isSigner: true, isWritable: true },
+        { pubkey: maker.publicKey, isSigner: true, isWritable: false },
+        { pubkey: anchor.web3.SystemProgram.programId, isSigner: false, isWritable: false },
+      ],
+      data,
+    });
+  };
+
   before(async () => {
     console.log("Funding test accounts from main provider wallet...");
     const transferTx = new anchor.web3.Transaction().add(
@@ -84,8 +213,194 @@ describe("capstone-auction", () => {
 
     await mintTo(provider.connection, maker.payer, bidMint, bidder1BidAta, maker.publicKey, 1000_000_000);
     await mintTo(provider.connection, maker.payer, bidMint, bidder2BidAta, maker.publicKey, 1000_000_000);
+
+    feeRecipientBidAta = getAssociatedTokenAddressSync(bidMint, feeRecipient.publicKey);
+
+    // The config is a singleton, so on a persistent cluster it may already exist
+    if (await provider.connection.getAccountInfo(configPda)) {
+      await program.methods.updateConfig(FEE_BPS, feeRecipient.publicKey, maker.publicKey)
+        .accountsStrict({ admin: maker.publicKey, config: configPda })
+        .rpc();
+    } else {
+      const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+      );
+      await program.methods.initializeConfig(FEE_BPS, feeRecipient.publicKey)
+        .accountsStrict({
+          admin: maker.publicKey,
+          config: configPda,
+          program: program.programId,
+          programData: programData,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
   });
 
+  // The (mint, vault, owner ATA) triplets that move a bundle's extra NFTs
+  const bundleAccountsFor = (
+    auction: anchor.web3.PublicKey,
+    mints: anchor.web3.PublicKey[],
+    owner: anchor.web3.PublicKey
+  ) =>
+    mints.flatMap((mint) => [
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: getAssociatedTokenAddressSync(mint, auction, true), isSigner: false, isWritable: true },
+      { pubkey: getAssociatedTokenAddressSync(mint, owner), isSigner: false, isWritable: true },
+    ]);
+
+  // Mints a fresh 1-of-1 NFT (or a lot of `prizeAmount` SFTs) to the maker and lists it in a new auction
+  const makeFreshAuction = async (seed: anchor.BN, auctionEndTime: number, opts: AuctionOptions = {}) => {
+    const freshNftMint = await createMint(provider.connection, maker.payer, maker.publicKey, null, 0);
+    const freshMakerNftAta = getAssociatedTokenAddressSync(freshNftMint, maker.publicKey);
+    const tx = new anchor.web3.Transaction().add(
+      createAssociatedTokenAccountInstruction(maker.publicKey, freshMakerNftAta, maker.publicKey, freshNftMint)
+    );
+    await provider.sendAndConfirm(tx);
+    await mintTo(provider.connection, maker.payer, freshNftMint, freshMakerNftAta, maker.publicKey, opts.prizeAmount ?? 1);
+    if (opts.royalties) {
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(createMetadataIx(freshNftMint, opts.royalties)));
+    }
+
+    const [auction] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auction"), maker.publicKey.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const freshVaultNft = getAssociatedTokenAddressSync(freshNftMint, auction, true);
+    // Native SOL auctions keep their bid-side accounts on the wrapped SOL mint
+    const freshBidMint = opts.nativeSol ? NATIVE_MINT : bidMint;
+    const freshVaultBid = getAssociatedTokenAddressSync(freshBidMint, auction, true);
+
+    // Extra NFTs for a bundle auction, passed as (mint, vault, maker ATA) triplets
+    const bundleMints: anchor.web3.PublicKey[] = [];
+    for (let i = 0; i < (opts.bundleSize ?? 0); i++) {
+      const mint = await createMint(provider.connection, maker.payer, maker.publicKey, null, 0);
+      const ata = getAssociatedTokenAddressSync(mint, maker.publicKey);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createAssociatedTokenAccountInstruction(maker.publicKey, ata, maker.publicKey, mint)
+        )
+      );
+      await mintTo(provider.connection, maker.payer, mint, ata, maker.publicKey, 1);
+      bundleMints.push(mint);
+    }
+
+    const createdSig = await program.methods
+      .makeAuction(
+        seed,
+        new anchor.BN(auctionEndTime),
+        new anchor.BN(opts.prizeAmount ?? 1),
+        opts.reservePrice ?? new anchor.BN(0),
+        opts.bidRules ?? openRules,
+        opts.softClose ?? noSoftClose,
+        opts.buyNowPrice ?? new anchor.BN(0),
+        opts.kind ?? english,
+        opts.settlementMode ?? firstPrice,
+        new anchor.BN(opts.startTime ?? 0),
+        opts.winners ?? 1,
+        opts.nativeSol ?? false,
+        opts.paymentMints ?? [],
+        opts.bundleSize ?? 0,
+        opts.requiredCollection ?? null,
+        opts.allowlistRoot ?? null
+      )
+      .accountsStrict({
+        maker: maker.publicKey,
+        nftMint: freshNftMint,
+        bidMint: freshBidMint,
+        makerNftAta: freshMakerNftAta,
+        auction: auction,
+        config: configPda,
+        vaultNft: freshVaultNft,
+        vaultBid: freshVaultBid,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(bundleAccountsFor(auction, bundleMints, maker.publicKey))
+      .rpc({ commitment: "confirmed" });
+
+    return {
+      createdSig,
+      auction,
+      nftMint: freshNftMint,
+      makerNftAta: freshMakerNftAta,
+      vaultNft: freshVaultNft,
+      vaultBid: freshVaultBid,
+      bundleMints,
+    };
+  };
+
+  // Decodes the Anchor events emitted by a confirmed transaction
+  const eventsIn = async (signature: string) => {
+    const txn = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    return [...parser.parseLogs(txn.meta.logMessages)];
+  };
+
+  const bidRecordFor = (auction: anchor.web3.PublicKey, bidder: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("bids"), auction.toBuffer(), bidder.toBuffer()],
+      program.programId
+    )[0];
+
+  const buyNow = (
+    listing: Awaited<ReturnType<typeof makeFreshAuction>>,
+    buyer: anchor.web3.Keypair,
+    buyerBidAta: anchor.web3.PublicKey
+  ) =>
+    program.methods.buyNow()
+      .accountsStrict({
+        buyer: buyer.publicKey,
+        maker: maker.publicKey,
+        auction: listing.auction,
+        bidRecord: bidRecordFor(listing.auction, buyer.publicKey),
+        buyerBidAta: buyerBidAta,
+        buyerNftAta: getAssociatedTokenAddressSync(listing.nftMint, buyer.publicKey),
+        makerBidAta: makerBidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        vaultNft: listing.vaultNft,
+        vaultBid: listing.vaultBid,
+        nftMint: listing.nftMint,
+        bidMint: bidMint,
+        nftMetadata: metadataPdaFor(listing.nftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc({ commitment: "confirmed" });
+
+  const placeBid = (
+    auction: anchor.web3.PublicKey,
+    auctionVaultBid: anchor.web3.PublicKey,
+    bidder: anchor.web3.Keypair,
+    bidderBidAta: anchor.web3.PublicKey,
+    amount: number
+  ) =>
+    program.methods.bid(new anchor.BN(amount), [])
+      .accountsStrict({
+        bidder: bidder.publicKey,
+        auction: auction,
+        bidRecord: bidRecordFor(auction, bidder.publicKey),
+        bidderBidAta: bidderBidAta,
+        vaultBid: auctionVaultBid,
+        bidMint: bidMint,
+        holderNftAccount: null,
+        holderNftMetadata: null,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .signers([bidder])
+      .rpc();
+
   it("Makes an auction", async () => {
     endTime = Math.floor(Date.now() / 1000) + 10;
 
@@ -97,13 +412,19 @@ describe("capstone-auction", () => {
     vaultBid = getAssociatedTokenAddressSync(bidMint, auctionPda, true);
 
     await program.methods
-      .makeAuction(seed1, new anchor.BN(endTime), new anchor.BN(1))
+      .makeAuction(seed1, new anchor.BN(endTime), new anchor.BN(1), new anchor.BN(0), {
+        startPrice: new anchor.BN(50_000_000),
+        minIncrement: new anchor.BN(0),
+        minIncrementBps: 1_000, // 10%
+        withdrawPenaltyBps: 0,
+      }, noSoftClose, new anchor.BN(0), english, firstPrice, new anchor.BN(0), 1, false, [], 0, null, null)
       .accountsStrict({
         maker: maker.publicKey,
         nftMint: nftMint,
         bidMint: bidMint,
         makerNftAta: makerNftAta,
         auction: auctionPda,
+        config: configPda,
         vaultNft: vaultNft,
         vaultBid: vaultBid,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -128,7 +449,7 @@ describe("capstone-auction", () => {
     );
 
     // Bidder 1 bids 100
-    await program.methods.bid(new anchor.BN(100_000_000))
+    await program.methods.bid(new anchor.BN(100_000_000), [])
       .accountsStrict({
         bidder: bidder1.publicKey,
         auction: auctionPda,
@@ -136,14 +457,17 @@ describe("capstone-auction", () => {
         bidderBidAta: bidder1BidAta,
         vaultBid: vaultBid,
         bidMint: bidMint,
+        holderNftAccount: null,
+        holderNftMetadata: null,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       })
       .signers([bidder1])
       .rpc();
 
     // Bidder 2 bids 200
-    await program.methods.bid(new anchor.BN(200_000_000))
+    await program.methods.bid(new anchor.BN(200_000_000), [])
       .accountsStrict({
         bidder: bidder2.publicKey,
         auction: auctionPda,
@@ -151,14 +475,17 @@ describe("capstone-auction", () => {
         bidderBidAta: bidder2BidAta,
         vaultBid: vaultBid,
         bidMint: bidMint,
+        holderNftAccount: null,
+        holderNftMetadata: null,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       })
       .signers([bidder2])
       .rpc();
 
     // Bidder 1 raises by 150 (Total 250)
-    await program.methods.bid(new anchor.BN(150_000_000))
+    await program.methods.bid(new anchor.BN(150_000_000), [])
       .accountsStrict({
         bidder: bidder1.publicKey,
         auction: auctionPda,
@@ -166,8 +493,11 @@ describe("capstone-auction", () => {
         bidderBidAta: bidder1BidAta,
         vaultBid: vaultBid,
         bidMint: bidMint,
+        holderNftAccount: null,
+        holderNftMetadata: null,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       })
       .signers([bidder1])
       .rpc();
@@ -177,6 +507,37 @@ describe("capstone-auction", () => {
     expect(auctionData.highestBidAmount.toNumber()).to.equal(250_000_000);
   });
 
+  it("Rejects bids that are empty or below the minimum increment", async () => {
+    const bidder2Accounts = {
+      bidder: bidder2.publicKey,
+      auction: auctionPda,
+      bidRecord: bidRecord2,
+      bidderBidAta: bidder2BidAta,
+      vaultBid: vaultBid,
+      bidMint: bidMint,
+      holderNftAccount: null,
+      holderNftMetadata: null,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    };
+
+    try {
+      await program.methods.bid(new anchor.BN(0), []).accountsStrict(bidder2Accounts).signers([bidder2]).rpc();
+      expect.fail("zero bid should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("ZeroBid");
+    }
+
+    // Bidder 2 raises 200 -> 260, but 10% over 250 requires at least 275
+    try {
+      await program.methods.bid(new anchor.BN(60_000_000), []).accountsStrict(bidder2Accounts).signers([bidder2]).rpc();
+      expect.fail("undersized raise should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("BidIncrementTooSmall");
+    }
+  });
+
   it("Resolves the auction via crank bot", async () => {
     // Wait for the auction timer to expire
     console.log("Waiting 15 seconds for auction to end...(10 sec delay + 5 sec buffer)");
@@ -184,7 +545,7 @@ describe("capstone-auction", () => {
 
     winnerNftAta = getAssociatedTokenAddressSync(nftMint, bidder1.publicKey);
 
-    await program.methods.resolveAuction()
+    const resolveSig = await program.methods.resolveAuction()
       .accountsStrict({
         resolver: crank.publicKey,
         auction: auctionPda,
@@ -192,21 +553,38 @@ describe("capstone-auction", () => {
         maker: maker.publicKey,
         winnerBidRecord: bidRecord1,
         makerBidAta: makerBidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        winnerBidAta: bidder1BidAta,
         winnerNftAta: winnerNftAta,
+        makerNftAta: makerNftAta,
         vaultNft: vaultNft,
         vaultBid: vaultBid,
+        primaryVaultBid: null,
         nftMint: nftMint,
         bidMint: bidMint,
+        nftMetadata: metadataPdaFor(nftMint),
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .signers([crank])
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
-    // Check Maker got the money
+    // Check the indexer-facing event matches the settlement
+    const [resolved] = await eventsIn(resolveSig);
+    expect(resolved.name).to.equal("auctionResolved");
+    expect(resolved.data.winner.toBase58()).to.equal(bidder1.publicKey.toBase58());
+    expect(resolved.data.amount.toNumber()).to.equal(250_000_000);
+    expect(resolved.data.fee.toNumber()).to.equal(12_500_000);
+    expect(resolved.data.reserveMet).to.be.true;
+
+    // Check Maker got the money, minus the 5% protocol fee
     const makerBidBalance = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
-    expect(makerBidBalance).to.equal(250); // 250_000_000 / 10^6
+    expect(makerBidBalance).to.equal(237.5); // 237_500_000 / 10^6
+    const feeBalance = (await provider.connection.getTokenAccountBalance(feeRecipientBidAta)).value.uiAmount;
+    expect(feeBalance).to.equal(12.5);
 
     // Check Winner got the NFT
     const winnerNftBalance = (await provider.connection.getTokenAccountBalance(winnerNftAta)).value.uiAmount;
@@ -229,6 +607,7 @@ describe("capstone-auction", () => {
         bidderBidAta: bidder2BidAta,
         vaultBid: vaultBid,
         bidMint: bidMint,
+        makerBidAta: makerBidAta,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -266,13 +645,14 @@ describe("capstone-auction", () => {
     const zeroVaultNft = getAssociatedTokenAddressSync(newNftMint, zeroAuctionPda, true);
     const zeroVaultBid = getAssociatedTokenAddressSync(bidMint, zeroAuctionPda, true);
 
-    await program.methods.makeAuction(seed2, new anchor.BN(fastEndTime), new anchor.BN(1))
+    await program.methods.makeAuction(seed2, new anchor.BN(fastEndTime), new anchor.BN(1), new anchor.BN(0), openRules, noSoftClose, new anchor.BN(0), english, firstPrice, new anchor.BN(0), 1, false, [], 0, null, null)
       .accountsStrict({
         maker: maker.publicKey,
         nftMint: newNftMint,
         bidMint: bidMint,
         makerNftAta: newMakerNftAta,
         auction: zeroAuctionPda,
+        config: configPda,
         vaultNft: zeroVaultNft,
         vaultBid: zeroVaultBid,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -306,4 +686,2409 @@ describe("capstone-auction", () => {
     expect(returnedBalance).to.equal(1);
     expect(cancelledAuctionInfo).to.be.null;
   });
-});
\ No newline at end of file
+
+  it("Returns the NFT and refunds the top bidder when the reserve is not met", async () => {
+    const reserveNftMint = await createMint(provider.connection, maker.payer, maker.publicKey, null, 0);
+    const reserveMakerNftAta = getAssociatedTokenAddressSync(reserveNftMint, maker.publicKey);
+    let tx = new anchor.web3.Transaction().add(
+      createAssociatedTokenAccountInstruction(maker.publicKey, reserveMakerNftAta, maker.publicKey, reserveNftMint)
+    );
+    await provider.sendAndConfirm(tx);
+    await mintTo(provider.connection, maker.payer, reserveNftMint, reserveMakerNftAta, maker.publicKey, 1);
+
+    const reserveEndTime = Math.floor(Date.now() / 1000) + 4;
+    const [reserveAuctionPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auction"), maker.publicKey.toBuffer(), seed3.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const reserveVaultNft = getAssociatedTokenAddressSync(reserveNftMint, reserveAuctionPda, true);
+    const reserveVaultBid = getAssociatedTokenAddressSync(bidMint, reserveAuctionPda, true);
+    const [reserveBidRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("bids"), reserveAuctionPda.toBuffer(), bidder2.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // Reserve of 500, but the only bid is 100
+    await program.methods.makeAuction(seed3, new anchor.BN(reserveEndTime), new anchor.BN(1), new anchor.BN(500_000_000), openRules, noSoftClose, new anchor.BN(0), english, firstPrice, new anchor.BN(0), 1, false, [], 0, null, null)
+      .accountsStrict({
+        maker: maker.publicKey,
+        nftMint: reserveNftMint,
+        bidMint: bidMint,
+        makerNftAta: reserveMakerNftAta,
+        auction: reserveAuctionPda,
+        config: configPda,
+        vaultNft: reserveVaultNft,
+        vaultBid: reserveVaultBid,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      }).rpc();
+
+    await program.methods.bid(new anchor.BN(100_000_000), [])
+      .accountsStrict({
+        bidder: bidder2.publicKey,
+        auction: reserveAuctionPda,
+        bidRecord: reserveBidRecord,
+        bidderBidAta: bidder2BidAta,
+        vaultBid: reserveVaultBid,
+        bidMint: bidMint,
+        holderNftAccount: null,
+        holderNftMetadata: null,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .signers([bidder2])
+      .rpc();
+
+    console.log("Waiting 6 seconds for reserve auction to end...(4 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    const sig = await program.methods.resolveAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: reserveAuctionPda,
+        winner: bidder2.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: reserveBidRecord,
+        makerBidAta: makerBidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        winnerBidAta: bidder2BidAta,
+        winnerNftAta: getAssociatedTokenAddressSync(reserveNftMint, bidder2.publicKey),
+        makerNftAta: reserveMakerNftAta,
+        vaultNft: reserveVaultNft,
+        vaultBid: reserveVaultBid,
+        primaryVaultBid: null,
+        nftMint: reserveNftMint,
+        bidMint: bidMint,
+        nftMetadata: metadataPdaFor(reserveNftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([crank])
+      .rpc({ commitment: "confirmed" });
+
+    // Nothing sold, so the event reports no sale amount
+    const [resolved] = await eventsIn(sig);
+    expect(resolved.data.reserveMet).to.be.false;
+    expect(resolved.data.amount.toNumber()).to.equal(0);
+
+    // NFT went back to the maker and the top bidder's record survived
+    const returnedBalance = (await provider.connection.getTokenAccountBalance(reserveMakerNftAta)).value.uiAmount;
+    expect(returnedBalance).to.equal(1);
+    expect(await provider.connection.getAccountInfo(reserveBidRecord)).to.not.be.null;
+
+    // The top bidder can now pull their funds out like any loser
+    await program.methods.claimRefund()
+      .accountsStrict({
+        bidder: bidder2.publicKey,
+        maker: maker.publicKey,
+        auction: reserveAuctionPda,
+        bidRecord: reserveBidRecord,
+        bidderBidAta: bidder2BidAta,
+        vaultBid: reserveVaultBid,
+        bidMint: bidMint,
+        makerBidAta: makerBidAta,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bidder2])
+      .rpc();
+
+    const bidder2Balance = (await provider.connection.getTokenAccountBalance(bidder2BidAta)).value.uiAmount;
+    expect(bidder2Balance).to.equal(1000);
+    expect(await provider.connection.getAccountInfo(reserveAuctionPda)).to.be.null;
+  });
+
+  it("Extends the deadline when the lead changes inside the soft-close window", async () => {
+    const snipeEndTime = Math.floor(Date.now() / 1000) + 5;
+    const sniped = await makeFreshAuction(seed4, snipeEndTime, {
+      softClose: { window: new anchor.BN(30), extension: new anchor.BN(10), maxExtension: new anchor.BN(15) },
+    });
+
+    // Each lead change lands inside the 30s window; the second one is capped at 15s total
+    await placeBid(sniped.auction, sniped.vaultBid, bidder1, bidder1BidAta, 10_000_000);
+    let auctionData = await program.account.auction.fetch(sniped.auction);
+    expect(auctionData.endTime.toNumber()).to.equal(snipeEndTime + 10);
+
+    await placeBid(sniped.auction, sniped.vaultBid, bidder2, bidder2BidAta, 20_000_000);
+    auctionData = await program.account.auction.fetch(sniped.auction);
+    expect(auctionData.endTime.toNumber()).to.equal(snipeEndTime + 15);
+    expect(auctionData.extendedBy.toNumber()).to.equal(15);
+  });
+
+  it("Settles instantly when a bidder meets the buy-now price", async () => {
+    const buyNowEndTime = Math.floor(Date.now() / 1000) + 60;
+    const listing = await makeFreshAuction(seed5, buyNowEndTime, { buyNowPrice: new anchor.BN(300_000_000) });
+    const buyerRecord = bidRecordFor(listing.auction, bidder1.publicKey);
+    const buyerNftAta = getAssociatedTokenAddressSync(listing.nftMint, bidder1.publicKey);
+
+    // Bidder 1 opens at 100, Bidder 2 outbids at 150
+    await placeBid(listing.auction, listing.vaultBid, bidder1, bidder1BidAta, 100_000_000);
+    await placeBid(listing.auction, listing.vaultBid, bidder2, bidder2BidAta, 150_000_000);
+
+    const makerBefore = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+
+    // Bidder 1's existing 100 counts toward the 300 buy-now price
+    await buyNow(listing, bidder1, bidder1BidAta);
+
+    const makerAfter = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+    expect(makerAfter - makerBefore).to.equal(285); // 300 minus the 5% fee
+    expect((await provider.connection.getTokenAccountBalance(buyerNftAta)).value.uiAmount).to.equal(1);
+    expect(await provider.connection.getAccountInfo(buyerRecord)).to.be.null;
+
+    // Bidder 2 does not have to wait for the original deadline
+    await program.methods.claimRefund()
+      .accountsStrict({
+        bidder: bidder2.publicKey,
+        maker: maker.publicKey,
+        auction: listing.auction,
+        bidRecord: bidRecordFor(listing.auction, bidder2.publicKey),
+        bidderBidAta: bidder2BidAta,
+        vaultBid: listing.vaultBid,
+        bidMint: bidMint,
+        makerBidAta: makerBidAta,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bidder2])
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(listing.auction)).to.be.null;
+  });
+
+  it("Turns buy-now off once open bids reach the buy-now price", async () => {
+    const listing = await makeFreshAuction(seed26, Math.floor(Date.now() / 1000) + 60, { buyNowPrice: new anchor.BN(100_000_000) });
+
+    // Bidder 2 goes past the buy-now price with an open bid
+    await placeBid(listing.auction, listing.vaultBid, bidder2, bidder2BidAta, 120_000_000);
+
+    // Bidder 1 can no longer buy at 100 and push the leader out
+    try {
+      await buyNow(listing, bidder1, bidder1BidAta);
+      expect.fail("buy-now should not undercut the leading bid");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("BuyNowOutbid");
+    }
+
+    const auctionData = await program.account.auction.fetch(listing.auction);
+    expect(auctionData.highestBidder.toBase58()).to.equal(bidder2.publicKey.toBase58());
+    expect(auctionData.resolved).to.be.false;
+  });
+
+  it("Pays creator royalties from the metadata before the maker", async () => {
+    const creatorA = anchor.web3.Keypair.generate().publicKey;
+    const creatorB = anchor.web3.Keypair.generate().publicKey;
+    const creatorAAta = getAssociatedTokenAddressSync(bidMint, creatorA);
+    const creatorBAta = getAssociatedTokenAddressSync(bidMint, creatorB);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(maker.publicKey, creatorAAta, creatorA, bidMint),
+        createAssociatedTokenAccountInstruction(maker.publicKey, creatorBAta, creatorB, bidMint)
+      )
+    );
+
+    // 10% royalties split 70/30 between two creators
+    const royaltyEndTime = Math.floor(Date.now() / 1000) + 4;
+    const royalty = await makeFreshAuction(seed6, royaltyEndTime, {
+      royalties: {
+        sellerFeeBasisPoints: 1_000,
+        creators: [
+          { address: creatorA, share: 70 },
+          { address: creatorB, share: 30 },
+        ],
+      },
+    });
+    await placeBid(royalty.auction, royalty.vaultBid, bidder1, bidder1BidAta, 100_000_000);
+
+    console.log("Waiting 6 seconds for royalty auction to end...(4 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    const makerBefore = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+
+    await program.methods.resolveAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: royalty.auction,
+        winner: bidder1.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(royalty.auction, bidder1.publicKey),
+        makerBidAta: makerBidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        winnerBidAta: bidder1BidAta,
+        winnerNftAta: getAssociatedTokenAddressSync(royalty.nftMint, bidder1.publicKey),
+        makerNftAta: royalty.makerNftAta,
+        vaultNft: royalty.vaultNft,
+        vaultBid: royalty.vaultBid,
+        primaryVaultBid: null,
+        nftMint: royalty.nftMint,
+        bidMint: bidMint,
+        nftMetadata: metadataPdaFor(royalty.nftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([
+        { pubkey: creatorAAta, isSigner: false, isWritable: true },
+        { pubkey: creatorBAta, isSigner: false, isWritable: true },
+      ])
+      .signers([crank])
+      .rpc();
+
+    // 100 sale: 7 + 3 royalties, 5 protocol fee, 85 to the maker
+    expect((await provider.connection.getTokenAccountBalance(creatorAAta)).value.uiAmount).to.equal(7);
+    expect((await provider.connection.getTokenAccountBalance(creatorBAta)).value.uiAmount).to.equal(3);
+    const makerAfter = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+    expect(makerAfter - makerBefore).to.equal(85);
+  });
+
+  it("Caps royalties at what the protocol fee leaves so settlement can't fail", async () => {
+    const creator = anchor.web3.Keypair.generate().publicKey;
+    const creatorAta = getAssociatedTokenAddressSync(bidMint, creator);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(maker.publicKey, creatorAta, creator, bidMint)
+      )
+    );
+
+    // 98% royalties plus the 5% protocol fee add up to more than the sale
+    const greedyEndTime = Math.floor(Date.now() / 1000) + 4;
+    const greedy = await makeFreshAuction(seed33, greedyEndTime, {
+      royalties: { sellerFeeBasisPoints: 9_800, creators: [{ address: creator, share: 100 }] },
+    });
+    await placeBid(greedy.auction, greedy.vaultBid, bidder1, bidder1BidAta, 100_000_000);
+
+    console.log("Waiting 6 seconds for the royalty-capped auction to end...(4 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    const makerBefore = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+    const sig = await program.methods.resolveAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: greedy.auction,
+        winner: bidder1.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(greedy.auction, bidder1.publicKey),
+        makerBidAta: makerBidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        winnerBidAta: bidder1BidAta,
+        winnerNftAta: getAssociatedTokenAddressSync(greedy.nftMint, bidder1.publicKey),
+        makerNftAta: greedy.makerNftAta,
+        vaultNft: greedy.vaultNft,
+        vaultBid: greedy.vaultBid,
+        primaryVaultBid: null,
+        nftMint: greedy.nftMint,
+        bidMint: bidMint,
+        nftMetadata: metadataPdaFor(greedy.nftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([{ pubkey: creatorAta, isSigner: false, isWritable: true }])
+      .signers([crank])
+      .rpc({ commitment: "confirmed" });
+
+    // 100 sale: 5 protocol fee, royalties capped at the remaining 95, nothing left for the maker
+    const [resolved] = await eventsIn(sig);
+    expect(resolved.data.fee.toNumber()).to.equal(5_000_000);
+    expect(resolved.data.royalties.toNumber()).to.equal(95_000_000);
+    expect((await provider.connection.getTokenAccountBalance(creatorAta)).value.uiAmount).to.equal(95);
+    const makerAfter = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+    expect(makerAfter - makerBefore).to.equal(0);
+    const winnerNftAta = getAssociatedTokenAddressSync(greedy.nftMint, bidder1.publicKey);
+    expect((await provider.connection.getTokenAccountBalance(winnerNftAta)).value.uiAmount).to.equal(1);
+  });
+
+  it("Settles at the fee in force when the auction was made", async () => {
+    const lockedEndTime = Math.floor(Date.now() / 1000) + 4;
+    const locked = await makeFreshAuction(seed34, lockedEndTime);
+    await placeBid(locked.auction, locked.vaultBid, bidder1, bidder1BidAta, 100_000_000);
+
+    // Fees above 10% are refused outright
+    try {
+      await program.methods.updateConfig(2_000, feeRecipient.publicKey, maker.publicKey)
+        .accountsStrict({ admin: maker.publicKey, config: configPda })
+        .rpc();
+      expect.fail("a 20% fee should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidFeeBps");
+    }
+
+    // Raising the fee mid-auction doesn't reach auctions that already exist
+    await program.methods.updateConfig(1_000, feeRecipient.publicKey, maker.publicKey)
+      .accountsStrict({ admin: maker.publicKey, config: configPda })
+      .rpc();
+
+    console.log("Waiting 6 seconds for the fee-locked auction to end...(4 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    try {
+      const sig = await program.methods.resolveAuction()
+        .accountsStrict({
+          resolver: crank.publicKey,
+          auction: locked.auction,
+          winner: bidder1.publicKey,
+          maker: maker.publicKey,
+          winnerBidRecord: bidRecordFor(locked.auction, bidder1.publicKey),
+          makerBidAta: makerBidAta,
+          config: configPda,
+          feeRecipient: feeRecipient.publicKey,
+          feeRecipientBidAta: feeRecipientBidAta,
+          winnerBidAta: bidder1BidAta,
+          winnerNftAta: getAssociatedTokenAddressSync(locked.nftMint, bidder1.publicKey),
+          makerNftAta: locked.makerNftAta,
+          vaultNft: locked.vaultNft,
+          vaultBid: locked.vaultBid,
+          primaryVaultBid: null,
+          nftMint: locked.nftMint,
+          bidMint: bidMint,
+          nftMetadata: metadataPdaFor(locked.nftMint),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([crank])
+        .rpc({ commitment: "confirmed" });
+
+      const [resolved] = await eventsIn(sig);
+      expect(resolved.data.fee.toNumber()).to.equal(5_000_000);
+    } finally {
+      await program.methods.updateConfig(FEE_BPS, feeRecipient.publicKey, maker.publicKey)
+        .accountsStrict({ admin: maker.publicKey, config: configPda })
+        .rpc();
+    }
+  });
+
+  it("Sells a Dutch auction to the first buyer at the decayed price", async () => {
+    // Price falls from 200 to 100 over 100 seconds, starting 50 seconds ago
+    const now = Math.floor(Date.now() / 1000);
+    const dutch = await makeFreshAuction(seed7, now + 50, {
+      startTime: now - 50,
+      kind: {
+        dutch: {
+          startPrice: new anchor.BN(200_000_000),
+          floorPrice: new anchor.BN(100_000_000),
+          stepInterval: new anchor.BN(0),
+        },
+      },
+    });
+
+    // Open bids are rejected on a Dutch auction
+    try {
+      await placeBid(dutch.auction, dutch.vaultBid, bidder2, bidder2BidAta, 150_000_000);
+      expect.fail("bid on a Dutch auction should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("NotEnglishAuction");
+    }
+
+    const sig = await buyNow(dutch, bidder2, bidder2BidAta);
+
+    // Roughly halfway through the decay, so the price is around 150
+    const [resolved] = await eventsIn(sig);
+    const price = resolved.data.amount.toNumber();
+    expect(price).to.be.lessThan(160_000_000);
+    expect(price).to.be.greaterThan(140_000_000);
+
+    const buyerNftAta = getAssociatedTokenAddressSync(dutch.nftMint, bidder2.publicKey);
+    expect((await provider.connection.getTokenAccountBalance(buyerNftAta)).value.uiAmount).to.equal(1);
+  });
+
+  it("Rejects a Dutch auction whose floor sits below the reserve", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    try {
+      await makeFreshAuction(seed32, now + 100, {
+        reservePrice: new anchor.BN(150_000_000),
+        kind: {
+          dutch: {
+            startPrice: new anchor.BN(200_000_000),
+            floorPrice: new anchor.BN(100_000_000),
+            stepInterval: new anchor.BN(0),
+          },
+        },
+      });
+      expect.fail("a floor below the reserve should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidDutchParams");
+    }
+  });
+
+  it("Rejects Dutch auctions priced at zero and sealed auctions with a buy-now price", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    try {
+      await makeFreshAuction(seed36, now + 100, {
+        kind: { dutch: { startPrice: new anchor.BN(0), floorPrice: new anchor.BN(0), stepInterval: new anchor.BN(0) } },
+      });
+      expect.fail("a Dutch auction that never asks for anything should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidDutchParams");
+    }
+
+    try {
+      await makeFreshAuction(seed36, now + 100, {
+        buyNowPrice: new anchor.BN(100_000_000),
+        kind: { sealed: { revealEnd: new anchor.BN(now + 200), unrevealedPenaltyBps: 0 } },
+      });
+      expect.fail("a sealed auction with a buy-now price should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidBuyNowPrice");
+    }
+  });
+
+  it("Rejects an all-zero sealed-bid commitment", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const sealed = await makeFreshAuction(seed35, now + 60, {
+      kind: { sealed: { revealEnd: new anchor.BN(now + 120), unrevealedPenaltyBps: 0 } },
+    });
+
+    try {
+      await program.methods.commitBid(new Array(32).fill(0), new anchor.BN(100_000_000), [])
+        .accountsStrict({
+          bidder: bidder1.publicKey,
+          auction: sealed.auction,
+          bidRecord: bidRecordFor(sealed.auction, bidder1.publicKey),
+          bidderBidAta: bidder1BidAta,
+          vaultBid: sealed.vaultBid,
+          bidMint: bidMint,
+          holderNftAccount: null,
+          holderNftMetadata: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+      expect.fail("a zero commitment should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidCommitment");
+    }
+  });
+
+  it("Runs a sealed-bid auction through commit, reveal and settlement", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const sealedEnd = now + 5;
+    const revealEnd = now + 10;
+    const sealed = await makeFreshAuction(seed8, sealedEnd, {
+      kind: { sealed: { revealEnd: new anchor.BN(revealEnd), unrevealedPenaltyBps: 1_000 } },
+    });
+
+    const commitmentFor = (bidder: anchor.web3.PublicKey, amount: anchor.BN, salt: Buffer) =>
+      [...createHash("sha256").update(bidder.toBuffer()).update(amount.toArrayLike(Buffer, "le", 8)).update(salt).digest()];
+
+    const commit = (bidder: anchor.web3.Keypair, bidderBidAta: anchor.web3.PublicKey, commitment: number[], deposit: number) =>
+      program.methods.commitBid(commitment, new anchor.BN(deposit), [])
+        .accountsStrict({
+          bidder: bidder.publicKey,
+          auction: sealed.auction,
+          bidRecord: bidRecordFor(sealed.auction, bidder.publicKey),
+          bidderBidAta: bidderBidAta,
+          vaultBid: sealed.vaultBid,
+          bidMint: bidMint,
+          holderNftAccount: null,
+          holderNftMetadata: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder])
+        .rpc();
+
+    // Bidder 1 hides a 120 bid behind a 150 deposit; Bidder 2 commits 100 and never reveals
+    const salt1 = randomBytes(32);
+    const bid1 = new anchor.BN(120_000_000);
+    await commit(bidder1, bidder1BidAta, commitmentFor(bidder1.publicKey, bid1, salt1), 150_000_000);
+    await commit(bidder2, bidder2BidAta, commitmentFor(bidder2.publicKey, new anchor.BN(90_000_000), randomBytes(32)), 100_000_000);
+
+    console.log("Waiting 6 seconds for the commit phase to end...");
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    await program.methods.revealBid(bid1, [...salt1])
+      .accountsStrict({
+        bidder: bidder1.publicKey,
+        auction: sealed.auction,
+        bidRecord: bidRecordFor(sealed.auction, bidder1.publicKey),
+      })
+      .signers([bidder1])
+      .rpc();
+
+    const auctionData = await program.account.auction.fetch(sealed.auction);
+    expect(auctionData.highestBidder.toBase58()).to.equal(bidder1.publicKey.toBase58());
+    expect(auctionData.highestBidAmount.toNumber()).to.equal(120_000_000);
+
+    console.log("Waiting 6 seconds for the reveal window to close...");
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    const balance = async (ata: anchor.web3.PublicKey) =>
+      (await provider.connection.getTokenAccountBalance(ata)).value.uiAmount;
+    const makerBefore = await balance(makerBidAta);
+    const bidder1Before = await balance(bidder1BidAta);
+
+    await program.methods.resolveAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: sealed.auction,
+        winner: bidder1.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(sealed.auction, bidder1.publicKey),
+        makerBidAta: makerBidAta,
+        winnerBidAta: bidder1BidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        winnerNftAta: getAssociatedTokenAddressSync(sealed.nftMint, bidder1.publicKey),
+        makerNftAta: sealed.makerNftAta,
+        vaultNft: sealed.vaultNft,
+        vaultBid: sealed.vaultBid,
+        primaryVaultBid: null,
+        nftMint: sealed.nftMint,
+        bidMint: bidMint,
+        nftMetadata: metadataPdaFor(sealed.nftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([crank])
+      .rpc();
+
+    // Winner pays 120 (114 to the maker after the 5% fee) and gets 30 of their deposit back
+    expect((await balance(makerBidAta)) - makerBefore).to.equal(114);
+    expect((await balance(bidder1BidAta)) - bidder1Before).to.equal(30);
+
+    // The unrevealed bidder loses 10% of their deposit to the maker
+    const bidder2Before = await balance(bidder2BidAta);
+    await program.methods.claimRefund()
+      .accountsStrict({
+        bidder: bidder2.publicKey,
+        maker: maker.publicKey,
+        auction: sealed.auction,
+        bidRecord: bidRecordFor(sealed.auction, bidder2.publicKey),
+        bidderBidAta: bidder2BidAta,
+        vaultBid: sealed.vaultBid,
+        bidMint: bidMint,
+        makerBidAta: makerBidAta,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bidder2])
+      .rpc();
+
+    expect((await balance(bidder2BidAta)) - bidder2Before).to.equal(90);
+    expect(await provider.connection.getAccountInfo(sealed.auction)).to.be.null;
+  });
+
+  it("Charges the winner the runner-up's bid under second-price settlement", async () => {
+    const vickreyEndTime = Math.floor(Date.now() / 1000) + 4;
+    const vickrey = await makeFreshAuction(seed9, vickreyEndTime, { settlementMode: { secondPrice: {} } });
+
+    await placeBid(vickrey.auction, vickrey.vaultBid, bidder1, bidder1BidAta, 100_000_000);
+    await placeBid(vickrey.auction, vickrey.vaultBid, bidder2, bidder2BidAta, 150_000_000);
+
+    const auctionData = await program.account.auction.fetch(vickrey.auction);
+    expect(auctionData.secondHighestBidAmount.toNumber()).to.equal(100_000_000);
+
+    console.log("Waiting 6 seconds for second-price auction to end...(4 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    const balance = async (ata: anchor.web3.PublicKey) =>
+      (await provider.connection.getTokenAccountBalance(ata)).value.uiAmount;
+    const makerBefore = await balance(makerBidAta);
+    const bidder2Before = await balance(bidder2BidAta);
+
+    await program.methods.resolveAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: vickrey.auction,
+        winner: bidder2.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(vickrey.auction, bidder2.publicKey),
+        makerBidAta: makerBidAta,
+        winnerBidAta: bidder2BidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        winnerNftAta: getAssociatedTokenAddressSync(vickrey.nftMint, bidder2.publicKey),
+        makerNftAta: vickrey.makerNftAta,
+        vaultNft: vickrey.vaultNft,
+        vaultBid: vickrey.vaultBid,
+        primaryVaultBid: null,
+        nftMint: vickrey.nftMint,
+        bidMint: bidMint,
+        nftMetadata: metadataPdaFor(vickrey.nftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([crank])
+      .rpc();
+
+    // Bidder 2 bid 150 but pays 100: 95 to the maker after the fee, 50 back to the winner
+    expect((await balance(makerBidAta)) - makerBefore).to.equal(95);
+    expect((await balance(bidder2BidAta)) - bidder2Before).to.equal(50);
+  });
+
+  it("Rejects bids before the auction's start time", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const delayed = await makeFreshAuction(seed10, now + 120, { startTime: now + 60 });
+
+    const auctionData = await program.account.auction.fetch(delayed.auction);
+    expect(auctionData.startTime.toNumber()).to.equal(now + 60);
+
+    try {
+      await placeBid(delayed.auction, delayed.vaultBid, bidder1, bidder1BidAta, 100_000_000);
+      expect.fail("bid before the start time should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("AuctionNotStarted");
+    }
+  });
+
+  it("Rejects auctions with an end time in the past or too far out", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const cases: [number, string][] = [
+      [now - 60, "EndTimeInPast"],
+      [now + 365 * 24 * 60 * 60, "AuctionTooLong"],
+    ];
+    for (const [badEndTime, code] of cases) {
+      try {
+        await makeFreshAuction(seed11, badEndTime);
+        expect.fail(`auction ending at ${badEndTime} should have been rejected`);
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      }
+    }
+  });
+
+  it("Settles the full quantity of a semi-fungible lot", async () => {
+    const lotEndTime = Math.floor(Date.now() / 1000) + 4;
+    const lot = await makeFreshAuction(seed12, lotEndTime, { prizeAmount: 10 });
+
+    expect((await program.account.auction.fetch(lot.auction)).prizeAmount.toNumber()).to.equal(10);
+    await placeBid(lot.auction, lot.vaultBid, bidder1, bidder1BidAta, 100_000_000);
+
+    console.log("Waiting 6 seconds for the lot auction to end...(4 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    const winnerNftAta = getAssociatedTokenAddressSync(lot.nftMint, bidder1.publicKey);
+    await program.methods.resolveAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: lot.auction,
+        winner: bidder1.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(lot.auction, bidder1.publicKey),
+        makerBidAta: makerBidAta,
+        winnerBidAta: bidder1BidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        winnerNftAta: winnerNftAta,
+        makerNftAta: lot.makerNftAta,
+        vaultNft: lot.vaultNft,
+        vaultBid: lot.vaultBid,
+        primaryVaultBid: null,
+        nftMint: lot.nftMint,
+        bidMint: bidMint,
+        nftMetadata: metadataPdaFor(lot.nftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([crank])
+      .rpc();
+
+    expect((await provider.connection.getTokenAccountBalance(winnerNftAta)).value.uiAmount).to.equal(10);
+    expect(await provider.connection.getAccountInfo(lot.vaultNft)).to.be.null;
+  });
+
+  it("Awards one unit to each of the top N bidders of a multi-winner auction", async () => {
+    // A third bidder so one of the three drops off a two-winner leaderboard
+    const bidder3 = anchor.web3.Keypair.generate();
+    const bidder3BidAta = getAssociatedTokenAddressSync(bidMint, bidder3.publicKey);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: maker.publicKey,
+          toPubkey: bidder3.publicKey,
+          lamports: 0.1 * anchor.web3.LAMPORTS_PER_SOL,
+        }),
+        createAssociatedTokenAccountInstruction(maker.publicKey, bidder3BidAta, bidder3.publicKey, bidMint)
+      )
+    );
+    await mintTo(provider.connection, maker.payer, bidMint, bidder3BidAta, maker.publicKey, 1000_000_000);
+
+    const dropEndTime = Math.floor(Date.now() / 1000) + 5;
+    const drop = await makeFreshAuction(seed13, dropEndTime, { prizeAmount: 2, winners: 2 });
+
+    await placeBid(drop.auction, drop.vaultBid, bidder1, bidder1BidAta, 100_000_000);
+    await placeBid(drop.auction, drop.vaultBid, bidder2, bidder2BidAta, 150_000_000);
+    await placeBid(drop.auction, drop.vaultBid, bidder3, bidder3BidAta, 120_000_000);
+
+    const auctionData = await program.account.auction.fetch(drop.auction);
+    expect(auctionData.leaderboard.map((entry) => entry.bidder.toBase58())).to.deep.equal([
+      bidder2.publicKey.toBase58(),
+      bidder3.publicKey.toBase58(),
+    ]);
+
+    console.log("Waiting 7 seconds for the multi-winner auction to end...(5 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 7000));
+
+    const claimPrize = (winner: anchor.web3.PublicKey) =>
+      program.methods.claimPrize()
+        .accountsStrict({
+          payer: crank.publicKey,
+          winner: winner,
+          maker: maker.publicKey,
+          auction: drop.auction,
+          winnerBidRecord: bidRecordFor(drop.auction, winner),
+          makerBidAta: makerBidAta,
+          config: configPda,
+          feeRecipient: feeRecipient.publicKey,
+          feeRecipientBidAta: feeRecipientBidAta,
+          winnerNftAta: getAssociatedTokenAddressSync(drop.nftMint, winner),
+          makerNftAta: drop.makerNftAta,
+          vaultNft: drop.vaultNft,
+          vaultBid: drop.vaultBid,
+          nftMint: drop.nftMint,
+          bidMint: bidMint,
+          nftMetadata: metadataPdaFor(drop.nftMint),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([crank])
+        .rpc();
+
+    // Bidder 1 was bumped off the leaderboard and has nothing to claim
+    try {
+      await claimPrize(bidder1.publicKey);
+      expect.fail("a bidder off the leaderboard should not be able to claim");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("NotAWinner");
+    }
+
+    const balance = async (ata: anchor.web3.PublicKey) =>
+      (await provider.connection.getTokenAccountBalance(ata)).value.uiAmount;
+    const makerBefore = await balance(makerBidAta);
+
+    await claimPrize(bidder2.publicKey);
+    await claimPrize(bidder3.publicKey);
+
+    // Each winner gets one unit and pays their own bid, minus the 5% fee for the maker
+    expect(await balance(getAssociatedTokenAddressSync(drop.nftMint, bidder2.publicKey))).to.equal(1);
+    expect(await balance(getAssociatedTokenAddressSync(drop.nftMint, bidder3.publicKey))).to.equal(1);
+    expect((await balance(makerBidAta)) - makerBefore).to.be.closeTo(256.5, 0.001);
+    expect(await provider.connection.getAccountInfo(drop.vaultNft)).to.be.null;
+
+    const bidder1Before = await balance(bidder1BidAta);
+    await program.methods.claimRefund()
+      .accountsStrict({
+        bidder: bidder1.publicKey,
+        maker: maker.publicKey,
+        auction: drop.auction,
+        bidRecord: bidRecordFor(drop.auction, bidder1.publicKey),
+        bidderBidAta: bidder1BidAta,
+        vaultBid: drop.vaultBid,
+        bidMint: bidMint,
+        makerBidAta: makerBidAta,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bidder1])
+      .rpc();
+
+    expect((await balance(bidder1BidAta)) - bidder1Before).to.equal(100);
+    expect(await provider.connection.getAccountInfo(drop.auction)).to.be.null;
+  });
+
+  it("Extends a multi-winner auction when any slot is taken late, but only flags top-slot changes as lead changes", async () => {
+    const dropEndTime = Math.floor(Date.now() / 1000) + 5;
+    const drop = await makeFreshAuction(seed30, dropEndTime, {
+      prizeAmount: 2,
+      winners: 2,
+      softClose: { window: new anchor.BN(30), extension: new anchor.BN(10), maxExtension: new anchor.BN(0) },
+    });
+    const bidPlacedIn = async (bidder: anchor.web3.Keypair, bidderBidAta: anchor.web3.PublicKey, amount: number) => {
+      const sig = await program.methods.bid(new anchor.BN(amount), [])
+        .accountsStrict({
+          bidder: bidder.publicKey,
+          auction: drop.auction,
+          bidRecord: bidRecordFor(drop.auction, bidder.publicKey),
+          bidderBidAta: bidderBidAta,
+          vaultBid: drop.vaultBid,
+          bidMint: bidMint,
+          holderNftAccount: null,
+          holderNftMetadata: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder])
+        .rpc({ commitment: "confirmed" });
+      return (await eventsIn(sig)).find((event) => event.name === "bidPlaced").data;
+    };
+
+    // Bidder 1 opens the book and takes the top slot
+    let placed = await bidPlacedIn(bidder1, bidder1BidAta, 100_000_000);
+    expect(placed.leaderChanged).to.be.true;
+    expect(placed.enteredLeaderboard).to.be.true;
+
+    // Bidder 2 takes the second slot: a winning slot, but not the lead
+    placed = await bidPlacedIn(bidder2, bidder2BidAta, 50_000_000);
+    expect(placed.leaderChanged).to.be.false;
+    expect(placed.enteredLeaderboard).to.be.true;
+
+    // Both entries landed inside the window, so each pushed the deadline back
+    let auctionData = await program.account.auction.fetch(drop.auction);
+    expect(auctionData.endTime.toNumber()).to.equal(dropEndTime + 20);
+
+    // Topping up to the top slot changes the lead but not who wins, so no extension
+    placed = await bidPlacedIn(bidder2, bidder2BidAta, 100_000_000);
+    expect(placed.leaderChanged).to.be.true;
+    expect(placed.enteredLeaderboard).to.be.false;
+    auctionData = await program.account.auction.fetch(drop.auction);
+    expect(auctionData.endTime.toNumber()).to.equal(dropEndTime + 20);
+  });
+
+  it("Settles every NFT of a bundle auction to the winner", async () => {
+    const bundleEndTime = Math.floor(Date.now() / 1000) + 5;
+    const bundle = await makeFreshAuction(seed14, bundleEndTime, { bundleSize: 2 });
+
+    const auctionData = await program.account.auction.fetch(bundle.auction);
+    expect(auctionData.bundleMints.map((mint) => mint.toBase58())).to.deep.equal(
+      bundle.bundleMints.map((mint) => mint.toBase58())
+    );
+    const [created] = await eventsIn(bundle.createdSig);
+    expect(created.name).to.equal("auctionCreated");
+    expect(created.data.bundleMints.map((mint) => mint.toBase58())).to.deep.equal(
+      bundle.bundleMints.map((mint) => mint.toBase58())
+    );
+
+    await placeBid(bundle.auction, bundle.vaultBid, bidder2, bidder2BidAta, 100_000_000);
+
+    console.log("Waiting 7 seconds for the bundle auction to end...(5 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 7000));
+
+    await program.methods.resolveAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: bundle.auction,
+        winner: bidder2.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(bundle.auction, bidder2.publicKey),
+        makerBidAta: makerBidAta,
+        winnerBidAta: bidder2BidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        winnerNftAta: getAssociatedTokenAddressSync(bundle.nftMint, bidder2.publicKey),
+        makerNftAta: bundle.makerNftAta,
+        vaultNft: bundle.vaultNft,
+        vaultBid: bundle.vaultBid,
+        primaryVaultBid: null,
+        nftMint: bundle.nftMint,
+        bidMint: bidMint,
+        nftMetadata: metadataPdaFor(bundle.nftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(bundleAccountsFor(bundle.auction, bundle.bundleMints, bidder2.publicKey))
+      .signers([crank])
+      .rpc();
+
+    for (const mint of [bundle.nftMint, ...bundle.bundleMints]) {
+      const winnerAta = getAssociatedTokenAddressSync(mint, bidder2.publicKey);
+      expect((await provider.connection.getTokenAccountBalance(winnerAta)).value.uiAmount).to.equal(1);
+      expect(await provider.connection.getAccountInfo(getAssociatedTokenAddressSync(mint, bundle.auction, true))).to.be.null;
+    }
+  });
+
+  it("Escrows and settles native SOL bids without wrapping", async () => {
+    const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        ...[bidder1, bidder2, crank].map((kp) =>
+          anchor.web3.SystemProgram.transfer({ fromPubkey: maker.publicKey, toPubkey: kp.publicKey, lamports: LAMPORTS })
+        )
+      )
+    );
+
+    const solEndTime = Math.floor(Date.now() / 1000) + 5;
+    const sol = await makeFreshAuction(seed15, solEndTime, { nativeSol: true });
+    const lamports = (key: anchor.web3.PublicKey) => provider.connection.getBalance(key);
+
+    // No token accounts on the bidder side: lamports move straight from the wallet
+    const placeSolBid = (bidder: anchor.web3.Keypair, amount: number) =>
+      program.methods.bid(new anchor.BN(amount), [])
+        .accountsStrict({
+          bidder: bidder.publicKey,
+          auction: sol.auction,
+          bidRecord: bidRecordFor(sol.auction, bidder.publicKey),
+          bidderBidAta: null,
+          vaultBid: sol.vaultBid,
+          bidMint: NATIVE_MINT,
+          holderNftAccount: null,
+          holderNftMetadata: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder])
+        .rpc();
+
+    const auctionLamportsBefore = await lamports(sol.auction);
+    await placeSolBid(bidder1, 0.2 * LAMPORTS);
+    await placeSolBid(bidder2, 0.3 * LAMPORTS);
+    expect((await lamports(sol.auction)) - auctionLamportsBefore).to.equal(0.5 * LAMPORTS);
+    expect((await program.account.auction.fetch(sol.auction)).escrowed.toNumber()).to.equal(0.5 * LAMPORTS);
+
+    console.log("Waiting 7 seconds for the native SOL auction to end...(5 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 7000));
+
+    const makerBefore = await lamports(maker.publicKey);
+    await program.methods.resolveAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: sol.auction,
+        winner: bidder2.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(sol.auction, bidder2.publicKey),
+        makerBidAta: getAssociatedTokenAddressSync(NATIVE_MINT, maker.publicKey),
+        winnerBidAta: getAssociatedTokenAddressSync(NATIVE_MINT, bidder2.publicKey),
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: getAssociatedTokenAddressSync(NATIVE_MINT, feeRecipient.publicKey),
+        winnerNftAta: getAssociatedTokenAddressSync(sol.nftMint, bidder2.publicKey),
+        makerNftAta: sol.makerNftAta,
+        vaultNft: sol.vaultNft,
+        vaultBid: sol.vaultBid,
+        primaryVaultBid: null,
+        nftMint: sol.nftMint,
+        bidMint: NATIVE_MINT,
+        nftMetadata: metadataPdaFor(sol.nftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([crank])
+      .rpc();
+
+    // The maker receives the winning 0.3 SOL minus the 5% fee, in lamports
+    expect((await lamports(maker.publicKey)) - makerBefore).to.equal(0.285 * LAMPORTS);
+
+    const bidder1Before = await lamports(bidder1.publicKey);
+    await program.methods.claimRefund()
+      .accountsStrict({
+        bidder: bidder1.publicKey,
+        maker: maker.publicKey,
+        auction: sol.auction,
+        bidRecord: bidRecordFor(sol.auction, bidder1.publicKey),
+        bidderBidAta: null,
+        vaultBid: sol.vaultBid,
+        bidMint: NATIVE_MINT,
+        makerBidAta: null,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bidder1])
+      .rpc();
+
+    // 0.2 SOL back plus the bid record's rent, less the transaction fee
+    expect((await lamports(bidder1.publicKey)) - bidder1Before).to.be.greaterThan(0.2 * LAMPORTS);
+    expect(await provider.connection.getAccountInfo(sol.auction)).to.be.null;
+  });
+
+  it("Accepts bids in a second payment mint and settles in the winner's mint", async () => {
+    // One base unit of the alternate mint counts as two of the primary bid mint
+    const altMint = await createMint(provider.connection, maker.payer, maker.publicKey, null, 6);
+    const bidder2AltAta = getAssociatedTokenAddressSync(altMint, bidder2.publicKey);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(maker.publicKey, bidder2AltAta, bidder2.publicKey, altMint)
+      )
+    );
+    await mintTo(provider.connection, maker.payer, altMint, bidder2AltAta, maker.publicKey, 100_000_000);
+
+    const multiEndTime = Math.floor(Date.now() / 1000) + 6;
+    const multi = await makeFreshAuction(seed16, multiEndTime, {
+      paymentMints: [{ mint: altMint, numerator: new anchor.BN(2), denominator: new anchor.BN(1) }],
+    });
+    const altVaultBid = getAssociatedTokenAddressSync(altMint, multi.auction, true);
+
+    await placeBid(multi.auction, multi.vaultBid, bidder1, bidder1BidAta, 100_000_000);
+    // 60 of the alternate mint is worth 120 of the bid mint, so bidder2 takes the lead
+    await program.methods.bid(new anchor.BN(60_000_000), [])
+      .accountsStrict({
+        bidder: bidder2.publicKey,
+        auction: multi.auction,
+        bidRecord: bidRecordFor(multi.auction, bidder2.publicKey),
+        bidderBidAta: bidder2AltAta,
+        vaultBid: altVaultBid,
+        bidMint: altMint,
+        holderNftAccount: null,
+        holderNftMetadata: null,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .signers([bidder2])
+      .rpc();
+
+    const state = await program.account.auction.fetch(multi.auction);
+    expect(state.highestBidder.toBase58()).to.equal(bidder2.publicKey.toBase58());
+    expect(state.highestBidAmount.toNumber()).to.equal(120_000_000);
+
+    console.log("Waiting 8 seconds for the multi-mint auction to end...(6 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 8000));
+
+    const makerAltAta = getAssociatedTokenAddressSync(altMint, maker.publicKey);
+    await program.methods.resolveAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: multi.auction,
+        winner: bidder2.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(multi.auction, bidder2.publicKey),
+        makerBidAta: makerAltAta,
+        winnerBidAta: bidder2AltAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: getAssociatedTokenAddressSync(altMint, feeRecipient.publicKey),
+        winnerNftAta: getAssociatedTokenAddressSync(multi.nftMint, bidder2.publicKey),
+        makerNftAta: multi.makerNftAta,
+        vaultNft: multi.vaultNft,
+        vaultBid: altVaultBid,
+        primaryVaultBid: multi.vaultBid,
+        nftMint: multi.nftMint,
+        bidMint: altMint,
+        nftMetadata: metadataPdaFor(multi.nftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([crank])
+      .rpc();
+
+    // The maker is paid in the mint the winner used: 60 less the 5% fee
+    const makerAltBalance = (await provider.connection.getTokenAccountBalance(makerAltAta)).value.uiAmount;
+    expect(makerAltBalance).to.equal(57);
+
+    // The loser is refunded from the primary mint's vault, which tears the auction down
+    const bidder1Before = (await provider.connection.getTokenAccountBalance(bidder1BidAta)).value.uiAmount;
+    await program.methods.claimRefund()
+      .accountsStrict({
+        bidder: bidder1.publicKey,
+        maker: maker.publicKey,
+        auction: multi.auction,
+        bidRecord: bidRecordFor(multi.auction, bidder1.publicKey),
+        bidderBidAta: bidder1BidAta,
+        vaultBid: multi.vaultBid,
+        bidMint: bidMint,
+        makerBidAta: makerBidAta,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bidder1])
+      .rpc();
+    const bidder1After = (await provider.connection.getTokenAccountBalance(bidder1BidAta)).value.uiAmount;
+    expect(bidder1After - bidder1Before).to.equal(100);
+    expect(await provider.connection.getAccountInfo(multi.auction)).to.be.null;
+  });
+
+  it("Closes the unused bid_mint vault when every bid is in another mint", async () => {
+    const altMint = await createMint(provider.connection, maker.payer, maker.publicKey, null, 6);
+    const bidder2AltAta = getAssociatedTokenAddressSync(altMint, bidder2.publicKey);
+    const makerAltAta = getAssociatedTokenAddressSync(altMint, maker.publicKey);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(maker.publicKey, bidder2AltAta, bidder2.publicKey, altMint)
+      )
+    );
+    await mintTo(provider.connection, maker.payer, altMint, bidder2AltAta, maker.publicKey, 100_000_000);
+
+    const altOnlyEndTime = Math.floor(Date.now() / 1000) + 4;
+    const altOnly = await makeFreshAuction(seed37, altOnlyEndTime, {
+      paymentMints: [{ mint: altMint, numerator: new anchor.BN(1), denominator: new anchor.BN(1) }],
+    });
+    const altVaultBid = getAssociatedTokenAddressSync(altMint, altOnly.auction, true);
+
+    await program.methods.bid(new anchor.BN(50_000_000), [])
+      .accountsStrict({
+        bidder: bidder2.publicKey,
+        auction: altOnly.auction,
+        bidRecord: bidRecordFor(altOnly.auction, bidder2.publicKey),
+        bidderBidAta: bidder2AltAta,
+        vaultBid: altVaultBid,
+        bidMint: altMint,
+        holderNftAccount: null,
+        holderNftMetadata: null,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .signers([bidder2])
+      .rpc();
+
+    console.log("Waiting 6 seconds for the payment-mint auction to end...(4 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    const resolveWith = (primaryVaultBid: anchor.web3.PublicKey | null) =>
+      program.methods.resolveAuction()
+        .accountsStrict({
+          resolver: crank.publicKey,
+          auction: altOnly.auction,
+          winner: bidder2.publicKey,
+          maker: maker.publicKey,
+          winnerBidRecord: bidRecordFor(altOnly.auction, bidder2.publicKey),
+          makerBidAta: makerAltAta,
+          winnerBidAta: bidder2AltAta,
+          config: configPda,
+          feeRecipient: feeRecipient.publicKey,
+          feeRecipientBidAta: getAssociatedTokenAddressSync(altMint, feeRecipient.publicKey),
+          winnerNftAta: getAssociatedTokenAddressSync(altOnly.nftMint, bidder2.publicKey),
+          makerNftAta: altOnly.makerNftAta,
+          vaultNft: altOnly.vaultNft,
+          vaultBid: altVaultBid,
+          primaryVaultBid,
+          nftMint: altOnly.nftMint,
+          bidMint: altMint,
+          nftMetadata: metadataPdaFor(altOnly.nftMint),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([crank])
+        .rpc();
+
+    try {
+      await resolveWith(null);
+      expect.fail("resolving without the bid_mint vault should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("MissingPrimaryVault");
+    }
+
+    // Nobody bid in bid_mint, so its vault closes at resolve and the maker gets the rent back
+    await resolveWith(altOnly.vaultBid);
+    expect(await provider.connection.getAccountInfo(altOnly.vaultBid)).to.be.null;
+  });
+
+  it("Rejects payment-mint bids that normalize to zero", async () => {
+    // A million base units of the alternate mint count as one of the bid mint
+    const dustMint = await createMint(provider.connection, maker.payer, maker.publicKey, null, 6);
+    const bidder1DustAta = getAssociatedTokenAddressSync(dustMint, bidder1.publicKey);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(maker.publicKey, bidder1DustAta, bidder1.publicKey, dustMint)
+      )
+    );
+    await mintTo(provider.connection, maker.payer, dustMint, bidder1DustAta, maker.publicKey, 1_000_000);
+
+    const dust = await makeFreshAuction(seed31, Math.floor(Date.now() / 1000) + 60, {
+      paymentMints: [{ mint: dustMint, numerator: new anchor.BN(1), denominator: new anchor.BN(1_000_000) }],
+    });
+
+    try {
+      await program.methods.bid(new anchor.BN(999_999), [])
+        .accountsStrict({
+          bidder: bidder1.publicKey,
+          auction: dust.auction,
+          bidRecord: bidRecordFor(dust.auction, bidder1.publicKey),
+          bidderBidAta: bidder1DustAta,
+          vaultBid: getAssociatedTokenAddressSync(dustMint, dust.auction, true),
+          bidMint: dustMint,
+          holderNftAccount: null,
+          holderNftMetadata: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+      expect.fail("a bid worth zero bid-mint units should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("BidRoundsToZero");
+    }
+    expect(await provider.connection.getAccountInfo(bidRecordFor(dust.auction, bidder1.publicKey))).to.be.null;
+  });
+
+  it("Credits only what lands in escrow for a Token-2022 mint with a transfer fee", async () => {
+    const T22 = TOKEN_2022_PROGRAM_ID;
+    const ata = (mint: anchor.web3.PublicKey, owner: anchor.web3.PublicKey) =>
+      getAssociatedTokenAddressSync(mint, owner, true, T22);
+    const createAta = (mint: anchor.web3.PublicKey, owner: anchor.web3.PublicKey) =>
+      createAssociatedTokenAccountInstruction(maker.publicKey, ata(mint, owner), owner, mint, T22);
+    const balance = async (account: anchor.web3.PublicKey) =>
+      Number((await provider.connection.getTokenAccountBalance(account)).value.amount);
+
+    // A 6-decimal bid mint that withholds 1% of every transfer
+    const feeMintKp = anchor.web3.Keypair.generate();
+    const feeMint = feeMintKp.publicKey;
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: maker.publicKey,
+          newAccountPubkey: feeMint,
+          space: mintLen,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: T22,
+        }),
+        createInitializeTransferFeeConfigInstruction(feeMint, maker.publicKey, maker.publicKey, 100, BigInt(1_000_000_000), T22),
+        createInitializeMintInstruction(feeMint, 6, maker.publicKey, null, T22)
+      ),
+      [feeMintKp]
+    );
+
+    // The prize has to live under the same token program as the bid mint
+    const t22NftMint = await createMint(provider.connection, maker.payer, maker.publicKey, null, 0, undefined, undefined, T22);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAta(t22NftMint, maker.publicKey),
+        createAta(feeMint, bidder1.publicKey),
+        createAta(feeMint, bidder2.publicKey)
+      )
+    );
+    await mintTo(provider.connection, maker.payer, t22NftMint, ata(t22NftMint, maker.publicKey), maker.publicKey, 1, [], undefined, T22);
+    for (const bidder of [bidder1, bidder2]) {
+      await mintTo(provider.connection, maker.payer, feeMint, ata(feeMint, bidder.publicKey), maker.publicKey, 1000_000_000, [], undefined, T22);
+    }
+
+    const [auction] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auction"), maker.publicKey.toBuffer(), seed17.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const feeVaultNft = ata(t22NftMint, auction);
+    const feeVaultBid = ata(feeMint, auction);
+
+    await program.methods
+      .makeAuction(seed17, new anchor.BN(Math.floor(Date.now() / 1000) + 6), new anchor.BN(1), new anchor.BN(0), openRules, noSoftClose, new anchor.BN(0), english, firstPrice, new anchor.BN(0), 1, false, [], 0, null, null)
+      .accountsStrict({
+        maker: maker.publicKey,
+        nftMint: t22NftMint,
+        bidMint: feeMint,
+        makerNftAta: ata(t22NftMint, maker.publicKey),
+        auction: auction,
+        config: configPda,
+        vaultNft: feeVaultNft,
+        vaultBid: feeVaultBid,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: T22,
+      })
+      .rpc();
+
+    for (const [bidder, amount] of [[bidder1, 100_000_000], [bidder2, 200_000_000]] as const) {
+      await program.methods.bid(new anchor.BN(amount), [])
+        .accountsStrict({
+          bidder: bidder.publicKey,
+          auction: auction,
+          bidRecord: bidRecordFor(auction, bidder.publicKey),
+          bidderBidAta: ata(feeMint, bidder.publicKey),
+          vaultBid: feeVaultBid,
+          bidMint: feeMint,
+          holderNftAccount: null,
+          holderNftMetadata: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: T22,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder])
+        .rpc();
+    }
+
+    // Each bid is credited net of the 1% fee, matching the vault exactly
+    expect((await program.account.bids.fetch(bidRecordFor(auction, bidder1.publicKey))).amount.toNumber()).to.equal(99_000_000);
+    const state = await program.account.auction.fetch(auction);
+    expect(state.highestBidAmount.toNumber()).to.equal(198_000_000);
+    expect(state.escrowed.toNumber()).to.equal(297_000_000);
+    expect(await balance(feeVaultBid)).to.equal(297_000_000);
+
+    console.log("Waiting 8 seconds for the transfer-fee auction to end...(6 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 8000));
+
+    await program.methods.resolveAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: auction,
+        winner: bidder2.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(auction, bidder2.publicKey),
+        makerBidAta: ata(feeMint, maker.publicKey),
+        winnerBidAta: ata(feeMint, bidder2.publicKey),
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: ata(feeMint, feeRecipient.publicKey),
+        winnerNftAta: ata(t22NftMint, bidder2.publicKey),
+        makerNftAta: ata(t22NftMint, maker.publicKey),
+        vaultNft: feeVaultNft,
+        vaultBid: feeVaultBid,
+        primaryVaultBid: null,
+        nftMint: t22NftMint,
+        bidMint: feeMint,
+        nftMetadata: metadataPdaFor(t22NftMint),
+        tokenProgram: T22,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([crank])
+      .rpc();
+
+    expect(await balance(ata(t22NftMint, bidder2.publicKey))).to.equal(1);
+    expect(await balance(feeVaultBid)).to.equal(99_000_000);
+
+    // The loser's refund drains the vault exactly, so teardown succeeds
+    const bidder1Before = await balance(ata(feeMint, bidder1.publicKey));
+    await program.methods.claimRefund()
+      .accountsStrict({
+        bidder: bidder1.publicKey,
+        maker: maker.publicKey,
+        auction: auction,
+        bidRecord: bidRecordFor(auction, bidder1.publicKey),
+        bidderBidAta: ata(feeMint, bidder1.publicKey),
+        vaultBid: feeVaultBid,
+        bidMint: feeMint,
+        makerBidAta: ata(feeMint, maker.publicKey),
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: T22,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bidder1])
+      .rpc();
+
+    // 99 back out of the vault, less the 1% fee on the way out
+    expect((await balance(ata(feeMint, bidder1.publicKey))) - bidder1Before).to.equal(98_010_000);
+    expect(await provider.connection.getAccountInfo(feeVaultBid)).to.be.null;
+    expect(await provider.connection.getAccountInfo(auction)).to.be.null;
+  });
+
+  it("Forwards transfer-hook accounts on every transfer path", async () => {
+    const hookProgram = anchor.workspace.transferHook as Program<TransferHook>;
+    const T22 = TOKEN_2022_PROGRAM_ID;
+    const ata = (mint: anchor.web3.PublicKey, owner: anchor.web3.PublicKey) =>
+      getAssociatedTokenAddressSync(mint, owner, true, T22);
+    const createAta = (mint: anchor.web3.PublicKey, owner: anchor.web3.PublicKey) =>
+      createAssociatedTokenAccountInstruction(maker.publicKey, ata(mint, owner), owner, mint, T22);
+    const hookPda = (label: string, mint: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync([Buffer.from(label), mint.toBuffer()], hookProgram.programId)[0];
+    // The hook program, its extra-account-metas PDA and the counter PDA it bumps
+    const hookAccountsFor = (mint: anchor.web3.PublicKey) => [
+      { pubkey: hookProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: hookPda("extra-account-metas", mint), isSigner: false, isWritable: false },
+      { pubkey: hookPda("counter", mint), isSigner: false, isWritable: true },
+    ];
+    const transfers = async (mint: anchor.web3.PublicKey) =>
+      (await hookProgram.account.counter.fetch(hookPda("counter", mint))).transfers.toNumber();
+
+    const createHookedMint = async (decimals: number) => {
+      const mintKp = anchor.web3.Keypair.generate();
+      const mintLen = getMintLen([ExtensionType.TransferHook]);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.createAccount({
+            fromPubkey: maker.publicKey,
+            newAccountPubkey: mintKp.publicKey,
+            space: mintLen,
+            lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+            programId: T22,
+          }),
+          createInitializeTransferHookInstruction(mintKp.publicKey, maker.publicKey, hookProgram.programId, T22),
+          createInitializeMintInstruction(mintKp.publicKey, decimals, maker.publicKey, null, T22)
+        ),
+        [mintKp]
+      );
+      await hookProgram.methods.initializeExtraAccountMetaList()
+        .accountsStrict({
+          payer: maker.publicKey,
+          extraAccountMetaList: hookPda("extra-account-metas", mintKp.publicKey),
+          counter: hookPda("counter", mintKp.publicKey),
+          mint: mintKp.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      return mintKp.publicKey;
+    };
+
+    // Both the prize and the bid mint run the hook
+    const hookNftMint = await createHookedMint(0);
+    const hookBidMint = await createHookedMint(6);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAta(hookNftMint, maker.publicKey),
+        createAta(hookBidMint, bidder1.publicKey),
+        createAta(hookBidMint, bidder2.publicKey)
+      )
+    );
+    await mintTo(provider.connection, maker.payer, hookNftMint, ata(hookNftMint, maker.publicKey), maker.publicKey, 1, [], undefined, T22);
+    for (const bidder of [bidder1, bidder2]) {
+      await mintTo(provider.connection, maker.payer, hookBidMint, ata(hookBidMint, bidder.publicKey), maker.publicKey, 1000_000_000, [], undefined, T22);
+    }
+
+    const [auction] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auction"), maker.publicKey.toBuffer(), seed18.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const hookVaultNft = ata(hookNftMint, auction);
+    const hookVaultBid = ata(hookBidMint, auction);
+
+    await program.methods
+      .makeAuction(seed18, new anchor.BN(Math.floor(Date.now() / 1000) + 6), new anchor.BN(1), new anchor.BN(0), openRules, noSoftClose, new anchor.BN(0), english, firstPrice, new anchor.BN(0), 1, false, [], 0, null, null)
+      .accountsStrict({
+        maker: maker.publicKey,
+        nftMint: hookNftMint,
+        bidMint: hookBidMint,
+        makerNftAta: ata(hookNftMint, maker.publicKey),
+        auction: auction,
+        config: configPda,
+        vaultNft: hookVaultNft,
+        vaultBid: hookVaultBid,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: T22,
+      })
+      .remainingAccounts(hookAccountsFor(hookNftMint))
+      .rpc();
+    expect(await transfers(hookNftMint)).to.equal(1);
+
+    for (const [bidder, amount] of [[bidder1, 100_000_000], [bidder2, 200_000_000]] as const) {
+      await program.methods.bid(new anchor.BN(amount), [])
+        .accountsStrict({
+          bidder: bidder.publicKey,
+          auction: auction,
+          bidRecord: bidRecordFor(auction, bidder.publicKey),
+          bidderBidAta: ata(hookBidMint, bidder.publicKey),
+          vaultBid: hookVaultBid,
+          bidMint: hookBidMint,
+          holderNftAccount: null,
+          holderNftMetadata: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: T22,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(hookAccountsFor(hookBidMint))
+        .signers([bidder])
+        .rpc();
+    }
+    expect(await transfers(hookBidMint)).to.equal(2);
+
+    console.log("Waiting 8 seconds for the transfer-hook auction to end...(6 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 8000));
+
+    // No bundle and no creators, so the hook accounts of both mints come first
+    await program.methods.resolveAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: auction,
+        winner: bidder2.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(auction, bidder2.publicKey),
+        makerBidAta: ata(hookBidMint, maker.publicKey),
+        winnerBidAta: ata(hookBidMint, bidder2.publicKey),
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: ata(hookBidMint, feeRecipient.publicKey),
+        winnerNftAta: ata(hookNftMint, bidder2.publicKey),
+        makerNftAta: ata(hookNftMint, maker.publicKey),
+        vaultNft: hookVaultNft,
+        vaultBid: hookVaultBid,
+        primaryVaultBid: null,
+        nftMint: hookNftMint,
+        bidMint: hookBidMint,
+        nftMetadata: metadataPdaFor(hookNftMint),
+        tokenProgram: T22,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([...hookAccountsFor(hookNftMint), ...hookAccountsFor(hookBidMint)])
+      .signers([crank])
+      .rpc();
+
+    // The NFT moves to the winner; the fee and the maker's share each move once
+    expect(await transfers(hookNftMint)).to.equal(2);
+    expect(await transfers(hookBidMint)).to.equal(4);
+    expect(Number((await provider.connection.getTokenAccountBalance(ata(hookNftMint, bidder2.publicKey))).value.amount)).to.equal(1);
+
+    await program.methods.claimRefund()
+      .accountsStrict({
+        bidder: bidder1.publicKey,
+        maker: maker.publicKey,
+        auction: auction,
+        bidRecord: bidRecordFor(auction, bidder1.publicKey),
+        bidderBidAta: ata(hookBidMint, bidder1.publicKey),
+        vaultBid: hookVaultBid,
+        bidMint: hookBidMint,
+        makerBidAta: ata(hookBidMint, maker.publicKey),
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: T22,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(hookAccountsFor(hookBidMint))
+      .signers([bidder1])
+      .rpc();
+
+    expect(await transfers(hookBidMint)).to.equal(5);
+    expect(await provider.connection.getAccountInfo(auction)).to.be.null;
+  });
+
+  it("Escrows a compressed NFT in its tree and settles it to the winner", async () => {
+    const BUBBLEGUM_ID = new anchor.web3.PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+    const SPL_NOOP_ID = new anchor.web3.PublicKey("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+    const COMPRESSION_ID = new anchor.web3.PublicKey("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+    const keccak = (...parts: Buffer[]) => Buffer.from(keccak_256(Buffer.concat(parts)));
+    const ixData = (name: string, ...args: Buffer[]) =>
+      Buffer.concat([createHash("sha256").update(`global:${name}`).digest().subarray(0, 8), ...args]);
+    const u32 = (n: number) => { const b = Buffer.alloc(4); b.writeUInt32LE(n); return b; };
+    const u64 = (n: number) => { const b = Buffer.alloc(8); b.writeBigUInt64LE(BigInt(n)); return b; };
+    const str = (v: string) => Buffer.concat([u32(v.length), Buffer.from(v)]);
+
+    // A depth-3 tree with room for 8 concurrent changes and no canopy
+    const MAX_DEPTH = 3;
+    const MAX_BUFFER = 8;
+    const nodeBytes = 32 * MAX_DEPTH + 40;
+    const treeSpace = 56 + 24 + MAX_BUFFER * nodeBytes + nodeBytes;
+    const treeKp = anchor.web3.Keypair.generate();
+    const tree = treeKp.publicKey;
+    const [treeConfig] = anchor.web3.PublicKey.findProgramAddressSync([tree.toBuffer()], BUBBLEGUM_ID);
+    const bubblegumKeys = {
+      treeConfig,
+      merkleTree: tree,
+      bubblegumProgram: BUBBLEGUM_ID,
+      logWrapper: SPL_NOOP_ID,
+      compressionProgram: COMPRESSION_ID,
+    };
+
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: maker.publicKey,
+          newAccountPubkey: tree,
+          space: treeSpace,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(treeSpace),
+          programId: COMPRESSION_ID,
+        }),
+        new anchor.web3.TransactionInstruction({
+          programId: BUBBLEGUM_ID,
+          keys: [
+            { pubkey: treeConfig, isSigner: false, isWritable: true },
+            { pubkey: tree, isSigner: false, isWritable: true },
+            { pubkey: maker.publicKey, isSigner: true, isWritable: true },
+            { pubkey: maker.publicKey, isSigner: true, isWritable: false },
+            { pubkey: SPL_NOOP_ID, isSigner: false, isWritable: false },
+            { pubkey: COMPRESSION_ID, isSigner: false, isWritable: false },
+            { pubkey: anchor.web3.SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: ixData("create_tree", u32(MAX_DEPTH), u32(MAX_BUFFER), Buffer.from([0])),
+        })
+      ),
+      [treeKp]
+    );
+
+    // A single unverified creator takes the whole 5% seller fee
+    const cnftCreator = anchor.web3.Keypair.generate().publicKey;
+    const cnftCreatorAta = getAssociatedTokenAddressSync(bidMint, cnftCreator);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(maker.publicKey, cnftCreatorAta, cnftCreator, bidMint)
+      )
+    );
+    const sellerFee = Buffer.from([0xf4, 0x01]); // 500 bps
+    const creatorBytes = Buffer.concat([cnftCreator.toBuffer(), Buffer.from([0, 100])]);
+
+    // MetadataArgs: name, symbol, uri, seller fee, primary sale, mutable,
+    // edition nonce, token standard, collection, uses, token program version, creators
+    const metadataArgs = Buffer.concat([
+      str("Capstone cNFT"), str("CNFT"), str("https://example.com/cnft.json"),
+      sellerFee, Buffer.from([0, 1]), Buffer.from([0]), Buffer.from([1, 0]),
+      Buffer.from([0]), Buffer.from([0]), Buffer.from([0]), u32(1), creatorBytes,
+    ]);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        new anchor.web3.TransactionInstruction({
+          programId: BUBBLEGUM_ID,
+          keys: [
+            { pubkey: treeConfig, isSigner: false, isWritable: true },
+            { pubkey: maker.publicKey, isSigner: false, isWritable: false },
+            { pubkey: maker.publicKey, isSigner: false, isWritable: false },
+            { pubkey: tree, isSigner: false, isWritable: true },
+            { pubkey: maker.publicKey, isSigner: true, isWritable: true },
+            { pubkey: maker.publicKey, isSigner: true, isWritable: false },
+            { pubkey: SPL_NOOP_ID, isSigner: false, isWritable: false },
+            { pubkey: COMPRESSION_ID, isSigner: false, isWritable: false },
+            { pubkey: anchor.web3.SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: ixData("mint_v1", metadataArgs),
+        })
+      )
+    );
+
+    // The first leaf of the tree: nonce and index 0. Its data and creator
+    // hashes are what Bubblegum derives from the metadata above.
+    const nonce = 0;
+    const [assetId] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("asset"), tree.toBuffer(), u64(nonce)],
+      BUBBLEGUM_ID
+    );
+    const metadataHash = keccak(metadataArgs);
+    const dataHash = keccak(metadataHash, sellerFee);
+    const creatorHash = keccak(creatorBytes);
+
+    // With only leaf 0 in the tree, every sibling on its path is an empty subtree
+    const emptyNodes = [Buffer.alloc(32)];
+    for (let i = 1; i < MAX_DEPTH; i++) emptyNodes.push(keccak(emptyNodes[i - 1], emptyNodes[i - 1]));
+    const proof = emptyNodes.map((node) => ({ pubkey: new anchor.web3.PublicKey(node), isSigner: false, isWritable: false }));
+    const rootFor = (owner: anchor.web3.PublicKey) => {
+      let node = keccak(Buffer.from([1]), assetId.toBuffer(), owner.toBuffer(), owner.toBuffer(), u64(nonce), dataHash, creatorHash);
+      for (const sibling of emptyNodes) node = keccak(node, sibling);
+      return node;
+    };
+    const onChainRoot = async () => {
+      const data = (await provider.connection.getAccountInfo(tree)).data;
+      const activeIndex = Number(data.readBigUInt64LE(56 + 8));
+      return data.subarray(56 + 24 + activeIndex * nodeBytes, 56 + 24 + activeIndex * nodeBytes + 32);
+    };
+    expect(Buffer.compare(await onChainRoot(), rootFor(maker.publicKey))).to.equal(0);
+
+    const [auction] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auction"), maker.publicKey.toBuffer(), seed19.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const cnftVaultBid = getAssociatedTokenAddressSync(bidMint, auction, true);
+    const leaf = { merkleTree: tree, dataHash: [...dataHash], creatorHash: [...creatorHash], nonce: new anchor.BN(nonce), index: nonce };
+
+    await program.methods
+      .makeCompressedAuction(seed19, new anchor.BN(Math.floor(Date.now() / 1000) + 6), new anchor.BN(0), openRules, noSoftClose, english, firstPrice, new anchor.BN(0), leaf, [...rootFor(maker.publicKey)], null, null)
+      .accountsStrict({
+        maker: maker.publicKey,
+        leafDelegate: maker.publicKey,
+        bidMint: bidMint,
+        auction: auction,
+        config: configPda,
+        vaultBid: cnftVaultBid,
+        ...bubblegumKeys,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(proof)
+      .rpc();
+
+    // The Auction PDA now owns the leaf, and is recorded against its asset id
+    expect(Buffer.compare(await onChainRoot(), rootFor(auction))).to.equal(0);
+    expect((await program.account.auction.fetch(auction)).nftMint.toBase58()).to.equal(assetId.toBase58());
+
+    await placeBid(auction, cnftVaultBid, bidder1, bidder1BidAta, 100_000_000);
+    await placeBid(auction, cnftVaultBid, bidder2, bidder2BidAta, 200_000_000);
+
+    console.log("Waiting 8 seconds for the compressed NFT auction to end...(6 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 8000));
+
+    // Settlement proves the royalty terms against the leaf's hashes
+    const leafRoyalties = {
+      metadataHash: [...metadataHash],
+      sellerFeeBasisPoints: 500,
+      creators: [{ address: cnftCreator, verified: false, share: 100 }],
+    };
+    const makerBefore = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+
+    // Terms that don't hash to the leaf can't dodge the royalty
+    try {
+      await program.methods.resolveCompressedAuction([...rootFor(auction)], proof.length, { ...leafRoyalties, sellerFeeBasisPoints: 0 })
+        .accountsStrict({
+          resolver: crank.publicKey,
+          auction: auction,
+          winner: bidder2.publicKey,
+          maker: maker.publicKey,
+          winnerBidRecord: bidRecordFor(auction, bidder2.publicKey),
+          makerBidAta: makerBidAta,
+          winnerBidAta: bidder2BidAta,
+          config: configPda,
+          feeRecipient: feeRecipient.publicKey,
+          feeRecipientBidAta: feeRecipientBidAta,
+          vaultBid: cnftVaultBid,
+          bidMint: bidMint,
+          ...bubblegumKeys,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(proof)
+        .signers([crank])
+        .rpc();
+      expect.fail("royalty terms that don't match the leaf should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("LeafRoyaltiesMismatch");
+    }
+
+    await program.methods.resolveCompressedAuction([...rootFor(auction)], proof.length, leafRoyalties)
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: auction,
+        winner: bidder2.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(auction, bidder2.publicKey),
+        makerBidAta: makerBidAta,
+        winnerBidAta: bidder2BidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        vaultBid: cnftVaultBid,
+        bidMint: bidMint,
+        ...bubblegumKeys,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([...proof, { pubkey: cnftCreatorAta, isSigner: false, isWritable: true }])
+      .signers([crank])
+      .rpc();
+
+    // The winner owns the leaf; of the 200 sale, 10 goes to the creator,
+    // 10 to the protocol and 180 to the maker
+    expect(Buffer.compare(await onChainRoot(), rootFor(bidder2.publicKey))).to.equal(0);
+    expect((await provider.connection.getTokenAccountBalance(cnftCreatorAta)).value.uiAmount).to.equal(10);
+    const makerAfter = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+    expect(makerAfter - makerBefore).to.equal(180);
+
+    await program.methods.claimRefund()
+      .accountsStrict({
+        bidder: bidder1.publicKey,
+        maker: maker.publicKey,
+        auction: auction,
+        bidRecord: bidRecordFor(auction, bidder1.publicKey),
+        bidderBidAta: bidder1BidAta,
+        vaultBid: cnftVaultBid,
+        bidMint: bidMint,
+        makerBidAta: makerBidAta,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bidder1])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(auction)).to.be.null;
+  });
+
+  const MPL_CORE_ID = new anchor.web3.PublicKey("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+  const str = (v: string) => { const len = Buffer.alloc(4); len.writeUInt32LE(v.length); return Buffer.concat([len, Buffer.from(v)]); };
+
+  // CreateV1 with no collection or separate update authority: Core takes its
+  // own id in place of each optional account left out. `plugins` are encoded
+  // PluginAuthorityPairs, each a Plugin followed by an optional authority.
+  const createAsset = async (...plugins: Buffer[]) => {
+    const asset = anchor.web3.Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        new anchor.web3.TransactionInstruction({
+          programId: MPL_CORE_ID,
+          keys: [
+            { pubkey: asset.publicKey, isSigner: true, isWritable: true },
+            { pubkey: MPL_CORE_ID, isSigner: false, isWritable: false },
+            { pubkey: MPL_CORE_ID, isSigner: false, isWritable: false },
+            { pubkey: maker.publicKey, isSigner: true, isWritable: true },
+            { pubkey: maker.publicKey, isSigner: false, isWritable: false },
+            { pubkey: MPL_CORE_ID, isSigner: false, isWritable: false },
+            { pubkey: anchor.web3.SystemProgram.programId, isSigner: false, isWritable: false },
+            { pubkey: MPL_CORE_ID, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([Buffer.from([0, 0]), str("Capstone Core"), str("https://example.com/core.json"), corePlugins(plugins)]),
+        })
+      ),
+      [asset]
+    );
+    return asset.publicKey;
+  };
+
+  const u32le = (n: number) => { const b = Buffer.alloc(4); b.writeUInt32LE(n); return b; };
+  const corePlugins = (plugins: Buffer[]) =>
+    plugins.length === 0 ? Buffer.from([0]) : Buffer.concat([Buffer.from([1]), u32le(plugins.length), ...plugins]);
+
+  // An AssetV1 account starts with its key byte followed by the owner
+  const ownerOf = async (asset: anchor.web3.PublicKey) =>
+    new anchor.web3.PublicKey((await provider.connection.getAccountInfo(asset)).data.subarray(1, 33));
+
+  const makeCoreAuction = async (
+    seed: anchor.BN,
+    asset: anchor.web3.PublicKey,
+    endIn: number,
+    gate: { requiredCollection?: anchor.web3.PublicKey; allowlistRoot?: number[] } = {}
+  ) => {
+    const [auction] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auction"), maker.publicKey.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const coreVaultBid = getAssociatedTokenAddressSync(bidMint, auction, true);
+    await program.methods
+      .makeCoreAuction(seed, new anchor.BN(Math.floor(Date.now() / 1000) + endIn), new anchor.BN(0), openRules, noSoftClose, english, firstPrice, new anchor.BN(0), gate.requiredCollection ?? null, gate.allowlistRoot ?? null)
+      .accountsStrict({
+        maker: maker.publicKey,
+        asset: asset,
+        collection: null,
+        bidMint: bidMint,
+        auction: auction,
+        config: configPda,
+        vaultBid: coreVaultBid,
+        coreProgram: MPL_CORE_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    return { auction, coreVaultBid };
+  };
+
+  it("Escrows a Core asset in the Auction PDA and settles it to the winner", async () => {
+    // A Royalties plugin paying 5% to a single creator, with no rule set
+    const coreCreator = anchor.web3.Keypair.generate().publicKey;
+    const coreCreatorAta = getAssociatedTokenAddressSync(bidMint, coreCreator);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(maker.publicKey, coreCreatorAta, coreCreator, bidMint)
+      )
+    );
+    const royaltiesPlugin = Buffer.concat([
+      Buffer.from([0, 0xf4, 0x01]), u32le(1), coreCreator.toBuffer(), Buffer.from([100]), Buffer.from([0]), Buffer.from([0]),
+    ]);
+    const asset = await createAsset(royaltiesPlugin);
+    const { auction, coreVaultBid } = await makeCoreAuction(seed20, asset, 6);
+
+    expect((await ownerOf(asset)).toBase58()).to.equal(auction.toBase58());
+    expect((await program.account.auction.fetch(auction)).nftMint.toBase58()).to.equal(asset.toBase58());
+
+    await placeBid(auction, coreVaultBid, bidder1, bidder1BidAta, 100_000_000);
+    await placeBid(auction, coreVaultBid, bidder2, bidder2BidAta, 200_000_000);
+
+    console.log("Waiting 8 seconds for the Core auction to end...(6 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 8000));
+
+    const makerBefore = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+    await program.methods.resolveCoreAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: auction,
+        winner: bidder2.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(auction, bidder2.publicKey),
+        makerBidAta: makerBidAta,
+        winnerBidAta: bidder2BidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        vaultBid: coreVaultBid,
+        bidMint: bidMint,
+        asset: asset,
+        collection: null,
+        coreProgram: MPL_CORE_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([{ pubkey: coreCreatorAta, isSigner: false, isWritable: true }])
+      .signers([crank])
+      .rpc();
+
+    // The winner owns the asset; of the 200 sale, 10 goes to the creator,
+    // 10 to the protocol and 180 to the maker
+    expect((await ownerOf(asset)).toBase58()).to.equal(bidder2.publicKey.toBase58());
+    expect((await provider.connection.getTokenAccountBalance(coreCreatorAta)).value.uiAmount).to.equal(10);
+    const makerAfter = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+    expect(makerAfter - makerBefore).to.equal(180);
+
+    await program.methods.claimRefund()
+      .accountsStrict({
+        bidder: bidder1.publicKey,
+        maker: maker.publicKey,
+        auction: auction,
+        bidRecord: bidRecordFor(auction, bidder1.publicKey),
+        bidderBidAta: bidder1BidAta,
+        vaultBid: coreVaultBid,
+        bidMint: bidMint,
+        makerBidAta: makerBidAta,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bidder1])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(auction)).to.be.null;
+  });
+
+  it("Refuses Core assets a permanent delegate could take back", async () => {
+    // PermanentTransferDelegate has no data; its authority defaults to the update authority
+    const asset = await createAsset(Buffer.from([7, 0]));
+    try {
+      await makeCoreAuction(seed27, asset, 60);
+      expect.fail("an asset with a permanent transfer delegate should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("CorePermanentDelegate");
+    }
+    expect((await ownerOf(asset)).toBase58()).to.equal(maker.publicKey.toBase58());
+  });
+
+  it("Gates bidding on a Core auction to holders of the required collection", async () => {
+    const asset = await createAsset();
+    const requiredCollection = anchor.web3.Keypair.generate().publicKey;
+    const { auction, coreVaultBid } = await makeCoreAuction(seed28, asset, 60, { requiredCollection });
+    expect((await program.account.auction.fetch(auction)).requiredCollection.toBase58()).to.equal(requiredCollection.toBase58());
+
+    // Without a holder token account and metadata the bid is turned away
+    try {
+      await placeBid(auction, coreVaultBid, bidder1, bidder1BidAta, 100_000_000);
+      expect.fail("a bid without proof of holding should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("MissingCollectionProof");
+    }
+  });
+
+  it("Restricts a Core auction to its allowlist", async () => {
+    // A one-bidder list, whose root is bidder 1's leaf and whose proof is empty
+    const leaf = createHash("sha256").update(Buffer.concat([Buffer.from([0]), bidder1.publicKey.toBuffer()])).digest();
+    const asset = await createAsset();
+    const { auction, coreVaultBid } = await makeCoreAuction(seed29, asset, 60, { allowlistRoot: [...leaf] });
+
+    try {
+      await placeBid(auction, coreVaultBid, bidder2, bidder2BidAta, 100_000_000);
+      expect.fail("an unlisted bidder should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("NotOnAllowlist");
+    }
+
+    await placeBid(auction, coreVaultBid, bidder1, bidder1BidAta, 100_000_000);
+    expect((await program.account.auction.fetch(auction)).highestBidder.toBase58()).to.equal(bidder1.publicKey.toBase58());
+  });
+
+  it("Returns a Core asset to the maker when the auction is cancelled", async () => {
+    const asset = await createAsset();
+    const { auction, coreVaultBid } = await makeCoreAuction(seed21, asset, 2);
+
+    console.log("Waiting 4 seconds for the Core auction to end...(2 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    await program.methods.cancelCoreAuction()
+      .accountsStrict({
+        maker: maker.publicKey,
+        auction: auction,
+        vaultBid: coreVaultBid,
+        bidMint: bidMint,
+        asset: asset,
+        collection: null,
+        coreProgram: MPL_CORE_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    expect((await ownerOf(asset)).toBase58()).to.equal(maker.publicKey.toBase58());
+    expect(await provider.connection.getAccountInfo(auction)).to.be.null;
+  });
+
+  it("Escrows a programmable NFT through Token Metadata and settles it to the winner", async () => {
+    const tmPda = (mint: anchor.web3.PublicKey, ...extra: Buffer[]) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer(), ...extra],
+        TOKEN_METADATA_PROGRAM_ID
+      )[0];
+    const editionPdaFor = (mint: anchor.web3.PublicKey) => tmPda(mint, Buffer.from("edition"));
+    const tokenRecordFor = (mint: anchor.web3.PublicKey, tokenAccount: anchor.web3.PublicKey) =>
+      tmPda(mint, Buffer.from("token_record"), tokenAccount.toBuffer());
+    const str = (v: string) => { const len = Buffer.alloc(4); len.writeUInt32LE(v.length); return Buffer.concat([len, Buffer.from(v)]); };
+    const account = (pubkey: anchor.web3.PublicKey, isSigner = false, isWritable = false) => ({ pubkey, isSigner, isWritable });
+    const SYSVAR_INSTRUCTIONS = anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY;
+
+    // Hand-encoded CreateV1 (a ProgrammableNonFungible with no rule set) and
+    // MintV1 of its single token to the maker. Optional accounts left out
+    // are passed as the Token Metadata program id.
+    const mintKp = anchor.web3.Keypair.generate();
+    const pnftMint = mintKp.publicKey;
+    const makerPnftAta = getAssociatedTokenAddressSync(pnftMint, maker.publicKey);
+    const amount = Buffer.alloc(8);
+    amount.writeBigUInt64LE(1n);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        new anchor.web3.TransactionInstruction({
+          programId: TOKEN_METADATA_PROGRAM_ID,
+          keys: [
+            account(metadataPdaFor(pnftMint), false, true),
+            account(editionPdaFor(pnftMint), false, true),
+            account(pnftMint, true, true),
+            account(maker.publicKey, true),
+            account(maker.publicKey, true, true),
+            account(maker.publicKey, true),
+            account(anchor.web3.SystemProgram.programId),
+            account(SYSVAR_INSTRUCTIONS),
+            account(TOKEN_PROGRAM_ID),
+          ],
+          data: Buffer.concat([
+            Buffer.from([42, 0]), // CreateV1
+            str("Capstone pNFT"), str("PNFT"), str(""),
+            Buffer.from([0, 0]), // seller fee
+            Buffer.from([0]), // no creators
+            Buffer.from([0, 1]), // primary sale, is_mutable
+            Buffer.from([4]), // ProgrammableNonFungible
+            Buffer.from([0, 0, 0, 0]), // no collection, uses, collection details or rule set
+            Buffer.from([1, 0]), // decimals: Some(0)
+            Buffer.from([1, 0]), // print supply: Some(Zero)
+          ]),
+        }),
+        new anchor.web3.TransactionInstruction({
+          programId: TOKEN_METADATA_PROGRAM_ID,
+          keys: [
+            account(makerPnftAta, false, true),
+            account(maker.publicKey),
+            account(metadataPdaFor(pnftMint)),
+            account(editionPdaFor(pnftMint), false, true),
+            account(tokenRecordFor(pnftMint, makerPnftAta), false, true),
+            account(pnftMint, false, true),
+            account(maker.publicKey, true),
+            account(TOKEN_METADATA_PROGRAM_ID),
+            account(maker.publicKey, true, true),
+            account(anchor.web3.SystemProgram.programId),
+            account(SYSVAR_INSTRUCTIONS),
+            account(TOKEN_PROGRAM_ID),
+            account(ASSOCIATED_TOKEN_PROGRAM_ID),
+            account(TOKEN_METADATA_PROGRAM_ID),
+            account(TOKEN_METADATA_PROGRAM_ID),
+          ],
+          data: Buffer.concat([Buffer.from([43, 0]), amount, Buffer.from([0])]), // MintV1
+        })
+      ),
+      [mintKp]
+    );
+
+    const [auction] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auction"), maker.publicKey.toBuffer(), seed22.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const pnftVaultNft = getAssociatedTokenAddressSync(pnftMint, auction, true);
+    const pnftVaultBid = getAssociatedTokenAddressSync(bidMint, auction, true);
+    const pnftKeys = {
+      nftMint: pnftMint,
+      nftMetadata: metadataPdaFor(pnftMint),
+      nftEdition: editionPdaFor(pnftMint),
+      vaultTokenRecord: tokenRecordFor(pnftMint, pnftVaultNft),
+      authorizationRulesProgram: null,
+      authorizationRules: null,
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS,
+    };
+
+    await program.methods
+      .makeProgrammableAuction(seed22, new anchor.BN(Math.floor(Date.now() / 1000) + 6), new anchor.BN(0), openRules, noSoftClose, english, firstPrice, new anchor.BN(0), null, null)
+      .accountsStrict({
+        maker: maker.publicKey,
+        bidMint: bidMint,
+        makerNftAta: makerPnftAta,
+        auction: auction,
+        config: configPda,
+        vaultNft: pnftVaultNft,
+        vaultBid: pnftVaultBid,
+        makerTokenRecord: tokenRecordFor(pnftMint, makerPnftAta),
+        ...pnftKeys,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        bidTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+      .rpc();
+
+    // The vault holds the pNFT, frozen under its new token record
+    const vaultAccount = await getAccount(provider.connection, pnftVaultNft);
+    expect(Number(vaultAccount.amount)).to.equal(1);
+    expect(vaultAccount.isFrozen).to.be.true;
+
+    await placeBid(auction, pnftVaultBid, bidder1, bidder1BidAta, 100_000_000);
+    await placeBid(auction, pnftVaultBid, bidder2, bidder2BidAta, 200_000_000);
+
+    console.log("Waiting 8 seconds for the pNFT auction to end...(6 sec delay + 2 sec buffer)");
+    await new Promise((resolve) => setTimeout(resolve, 8000));
+
+    const winnerPnftAta = getAssociatedTokenAddressSync(pnftMint, bidder2.publicKey);
+    const makerBefore = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+    await program.methods.resolveProgrammableAuction()
+      .accountsStrict({
+        resolver: crank.publicKey,
+        auction: auction,
+        winner: bidder2.publicKey,
+        maker: maker.publicKey,
+        winnerBidRecord: bidRecordFor(auction, bidder2.publicKey),
+        makerBidAta: makerBidAta,
+        winnerBidAta: bidder2BidAta,
+        config: configPda,
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientBidAta: feeRecipientBidAta,
+        vaultBid: pnftVaultBid,
+        bidMint: bidMint,
+        vaultNft: pnftVaultNft,
+        prizeReceiverAta: winnerPnftAta,
+        prizeReceiverTokenRecord: tokenRecordFor(pnftMint, winnerPnftAta),
+        ...pnftKeys,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        bidTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+      .signers([crank])
+      .rpc();
+
+    // The winner holds the pNFT, the vault is gone and the maker is paid 200 less the 5% fee
+    expect((await provider.connection.getTokenAccountBalance(winnerPnftAta)).value.uiAmount).to.equal(1);
+    expect(await provider.connection.getAccountInfo(pnftVaultNft)).to.be.null;
+    const makerAfter = (await provider.connection.getTokenAccountBalance(makerBidAta)).value.uiAmount;
+    expect(makerAfter - makerBefore).to.equal(190);
+
+    await program.methods.claimRefund()
+      .accountsStrict({
+        bidder: bidder1.publicKey,
+        maker: maker.publicKey,
+        auction: auction,
+        bidRecord: bidRecordFor(auction, bidder1.publicKey),
+        bidderBidAta: bidder1BidAta,
+        vaultBid: pnftVaultBid,
+        bidMint: bidMint,
+        makerBidAta: makerBidAta,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bidder1])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(auction)).to.be.null;
+  });
+
+  it("Only accepts bids from holders of the required collection", async () => {
+    const editionPdaFor = (mint: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer(), Buffer.from("edition")],
+        TOKEN_METADATA_PROGRAM_ID
+      )[0];
+    const makerRoyalties = { sellerFeeBasisPoints: 0, creators: [{ address: maker.publicKey, share: 100 }] };
+    const mintNftTo = async (owner: anchor.web3.PublicKey) => {
+      const mint = await createMint(provider.connection, maker.payer, maker.publicKey, null, 0);
+      const ata = getAssociatedTokenAddressSync(mint, owner);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(createAssociatedTokenAccountInstruction(maker.publicKey, ata, owner, mint))
+      );
+      await mintTo(provider.connection, maker.payer, mint, ata, maker.publicKey, 1);
+      return { mint, ata };
+    };
+
+    // The collection NFT needs a master edition before members can be verified against it
+    const collection = await mintNftTo(maker.publicKey);
+    const maxSupply = Buffer.alloc(8);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createMetadataIx(collection.mint, makerRoyalties),
+        new anchor.web3.TransactionInstruction({
+          programId: TOKEN_METADATA_PROGRAM_ID,
+          keys: [
+            { pubkey: editionPdaFor(collection.mint), isSigner: false, isWritable: true },
+            { pubkey: collection.mint, isSigner: false, isWritable: true },
+            { pubkey: maker.publicKey, isSigner: true, isWritable: false },
+            { pubkey: maker.publicKey, isSigner: true, isWritable: false },
+            { pubkey: maker.publicKey, isSigner: true, isWritable: true },
+            { pubkey: metadataPdaFor(collection.mint), isSigner: false, isWritable: true },
+            { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+            { pubkey: anchor.web3.SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([Buffer.from([17, 1]), maxSupply]), // CreateMasterEditionV3, max supply 0
+        })
+      )
+    );
+
+    // Bidder 1 holds a verified member of the collection
+    const member = await mintNftTo(bidder1.publicKey);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createMetadataIx(member.mint, makerRoyalties, collection.mint),
+        new anchor.web3.TransactionInstruction({
+          programId: TOKEN_METADATA_PROGRAM_ID,
+          keys: [
+            { pubkey: metadataPdaFor(member.mint), isSigner: false, isWritable: true },
+            { pubkey: maker.publicKey, isSigner: true, isWritable: true },
+            { pubkey: maker.publicKey, isSigner: true, isWritable: true },
+            { pubkey: collection.mint, isSigner: false, isWritable: false },
+            { pubkey: metadataPdaFor(collection.mint), isSigner: false, isWritable: false },
+            { pubkey: editionPdaFor(collection.mint), isSigner: false, isWritable: false },
+            { pubkey: TOKEN_METADATA_PROGRAM_ID, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.from([18]), // VerifyCollection
+        })
+      )
+    );
+
+    const gated = await makeFreshAuction(seed23, Math.floor(Date.now() / 1000) + 60, { requiredCollection: collection.mint });
+    expect((await program.account.auction.fetch(gated.auction)).requiredCollection.toBase58()).to.equal(collection.mint.toBase58());
+
+    const gatedBid = (
+      bidder: anchor.web3.Keypair,
+      bidderBidAta: anchor.web3.PublicKey,
+      holderNftAccount: anchor.web3.PublicKey | null,
+      holderNftMetadata: anchor.web3.PublicKey | null
+    ) =>
+      program.methods.bid(new anchor.BN(100_000_000), [])
+        .accountsStrict({
+          bidder: bidder.publicKey,
+          auction: gated.auction,
+          bidRecord: bidRecordFor(gated.auction, bidder.publicKey),
+          bidderBidAta: bidderBidAta,
+          vaultBid: gated.vaultBid,
+          bidMint: bidMint,
+          holderNftAccount,
+          holderNftMetadata,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder])
+        .rpc();
+
+    try {
+      await gatedBid(bidder2, bidder2BidAta, null, null);
+      expect.fail("a bid without a holder proof should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("MissingCollectionProof");
+    }
+
+    // Presenting someone else's NFT doesn't count
+    try {
+      await gatedBid(bidder2, bidder2BidAta, member.ata, metadataPdaFor(member.mint));
+      expect.fail("a bid on another holder's NFT should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("NotCollectionHolder");
+    }
+
+    // Neither does an NFT outside the collection
+    const outsider = await mintNftTo(bidder2.publicKey);
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(createMetadataIx(outsider.mint, makerRoyalties)));
+    try {
+      await gatedBid(bidder2, bidder2BidAta, outsider.ata, metadataPdaFor(outsider.mint));
+      expect.fail("a bid on an NFT outside the collection should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("NotCollectionHolder");
+    }
+
+    await gatedBid(bidder1, bidder1BidAta, member.ata, metadataPdaFor(member.mint));
+    const auctionData = await program.account.auction.fetch(gated.auction);
+    expect(auctionData.highestBidder.toBase58()).to.equal(bidder1.publicKey.toBase58());
+  });
+
+  it("Only accepts bids from bidders on the allowlist", async () => {
+    // Same hashing as Auction::allowlist_leaf / allowlist_node
+    const sha256 = (...parts: Buffer[]) => createHash("sha256").update(Buffer.concat(parts)).digest();
+    const leafFor = (bidder: anchor.web3.PublicKey) => sha256(Buffer.from([0]), bidder.toBuffer());
+    const nodeFor = (a: Buffer, b: Buffer) =>
+      Buffer.compare(a, b) <= 0 ? sha256(Buffer.from([1]), a, b) : sha256(Buffer.from([1]), b, a);
+
+    // A two-bidder list: each bidder's proof is the other's leaf
+    const root = nodeFor(leafFor(bidder1.publicKey), leafFor(crank.publicKey));
+    const privateSale = await makeFreshAuction(seed24, Math.floor(Date.now() / 1000) + 60, { allowlistRoot: [...root] });
+
+    const allowlistedBid = (bidder: anchor.web3.Keypair, bidderBidAta: anchor.web3.PublicKey, proof: Buffer[]) =>
+      program.methods.bid(new anchor.BN(100_000_000), proof.map((node) => [...node]))
+        .accountsStrict({
+          bidder: bidder.publicKey,
+          auction: privateSale.auction,
+          bidRecord: bidRecordFor(privateSale.auction, bidder.publicKey),
+          bidderBidAta: bidderBidAta,
+          vaultBid: privateSale.vaultBid,
+          bidMint: bidMint,
+          holderNftAccount: null,
+          holderNftMetadata: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder])
+        .rpc();
+
+    // Bidder 2 isn't listed, and borrowing bidder 1's proof doesn't help
+    try {
+      await allowlistedBid(bidder2, bidder2BidAta, [leafFor(crank.publicKey)]);
+      expect.fail("an unlisted bidder should have been rejected");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("NotOnAllowlist");
+    }
+
+    await allowlistedBid(bidder1, bidder1BidAta, [leafFor(crank.publicKey)]);
+    const auctionData = await program.account.auction.fetch(privateSale.auction);
+    expect(auctionData.highestBidder.toBase58()).to.equal(bidder1.publicKey.toBase58());
+  });
+
+  it("Lets an outbid bidder withdraw early for a penalty", async () => {
+    const fresh = await makeFreshAuction(seed25, Math.floor(Date.now() / 1000) + 60, {
+      bidRules: { ...openRules, withdrawPenaltyBps: 1_000 }, // 10%
+    });
+
+    const placeBid = (bidder: anchor.web3.Keypair, bidderBidAta: anchor.web3.PublicKey, amount: number) =>
+      program.methods.bid(new anchor.BN(amount), [])
+        .accountsStrict({
+          bidder: bidder.publicKey,
+          auction: fresh.auction,
+          bidRecord: bidRecordFor(fresh.auction, bidder.publicKey),
+          bidderBidAta: bidderBidAta,
+          vaultBid: fresh.vaultBid,
+          bidMint: bidMint,
+          holderNftAccount: null,
+          holderNftMetadata: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder])
+        .rpc();
+
+    const withdraw = (bidder: anchor.web3.Keypair, bidderBidAta: anchor.web3.PublicKey) =>
+      program.methods.withdrawBid()
+        .accountsStrict({
+          bidder: bidder.publicKey,
+          maker: maker.publicKey,
+          auction: fresh.auction,
+          bidRecord: bidRecordFor(fresh.auction, bidder.publicKey),
+          bidderBidAta: bidderBidAta,
+          vaultBid: fresh.vaultBid,
+          bidMint: bidMint,
+          makerBidAta: makerBidAta,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([bidder])
+        .rpc();
+
+    const balanceOf = async (ata: anchor.web3.PublicKey) =>
+      Number((await provider.connection.getTokenAccountBalance(ata)).value.amount);
+
+    await placeBid(bidder1, bidder1BidAta, 100_000_000);
+    await placeBid(bidder2, bidder2BidAta, 200_000_000);
+
+    // The leader is committed until the auction ends
+    try {
+      await withdraw(bidder2, bidder2BidAta);
+      expect.fail("the leading bid should not have been withdrawn");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("CannotWithdrawBid");
+    }
+
+    const bidder1Before = await balanceOf(bidder1BidAta);
+    const makerBefore = await balanceOf(makerBidAta);
+    await withdraw(bidder1, bidder1BidAta);
+
+    // 10% of the outbid deposit stays with the maker, the rest goes back
+    expect(await balanceOf(bidder1BidAta) - bidder1Before).to.equal(90_000_000);
+    expect(await balanceOf(makerBidAta) - makerBefore).to.equal(10_000_000);
+    expect(await provider.connection.getAccountInfo(bidRecordFor(fresh.auction, bidder1.publicKey))).to.be.null;
+
+    const auctionData = await program.account.auction.fetch(fresh.auction);
+    expect(auctionData.highestBidder.toBase58()).to.equal(bidder2.publicKey.toBase58());
+    expect(auctionData.escrowed.toNumber()).to.equal(200_000_000);
+  });
+});